@@ -73,10 +73,11 @@ async function queryFinishedLobbies(walletAddress: string): Promise<Prize[]> {
           
           try {
             // Query all finished lobbies where winner matches wallet address
+            // (settling and settled lobbies have a result too)
             const allLobbies = Array.from(ctx.db.lobby.iter());
             const finishedLobbies = allLobbies.filter(
               (lobby: any) =>
-                lobby.status === "finished" &&
+                ["Finished", "Settling", "Settled"].includes(lobby.status.tag) &&
                 lobby.winnerAddress?.toLowerCase() === walletAddress.toLowerCase()
             );
 
@@ -93,7 +94,7 @@ async function queryFinishedLobbies(walletAddress: string): Promise<Prize[]> {
                 matchId: Number(lobby.matchId),
                 arenaAddress: lobby.arenaAddress,
                 prizePool,
-                entryFee: lobby.entryFee || "0",
+                entryFee: lobby.entryFee?.toString() || "0",
                 maxPlayers,
                 createdAt: lobby.createdAt?.toString() || new Date().toISOString(),
               };
@@ -111,7 +112,8 @@ async function queryFinishedLobbies(walletAddress: string): Promise<Prize[]> {
           subscription.unsubscribe();
           reject(err);
         })
-        .subscribe(["SELECT * FROM lobby WHERE status = 'finished'"]);
+        // status is an enum, so finished lobbies are filtered client-side
+        .subscribe(["SELECT * FROM lobby"]);
     });
   } catch (error) {
     console.error("Error querying SpacetimeDB:", error);
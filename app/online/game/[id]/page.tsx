@@ -5,7 +5,7 @@ import { useParams, useSearchParams, useRouter } from "next/navigation";
 import { useAccount, useReadContract } from "wagmi";
 import { BoardRenderer } from "@/components/game/BoardRenderer";
 import { Button } from "@/components/ui/Button";
-import { useLobby, useSpacetimeConnection } from "@/hooks/useSpacetimeDB";
+import { hasResult, useLobby, useSpacetimeConnection } from "@/hooks/useSpacetimeDB";
 import { useVisualBoard } from "@/hooks/useVisualBoard";
 import { formatUSDC, formatPrize, ARENA_ADDRESSES } from "@/lib/contracts";
 import { onchainReactionAbi } from "@/lib/onchainReaction";
@@ -182,7 +182,7 @@ function OnlineGameContent() {
 
   // Check for winner and trigger backend finalization
   useEffect(() => {
-    if (lobby?.status === "finished" && lobby.winnerAddress && !hasTriggeredFinalize) {
+    if (hasResult(lobby?.status) && lobby.winnerAddress && !hasTriggeredFinalize) {
       // Clear any pending animations immediately
       clearExplosionQueue();
 
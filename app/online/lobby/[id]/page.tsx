@@ -14,7 +14,7 @@ import { ARENA_ADDRESSES, CHAIN_IDS, getChainName, formatUSDC, parseUSDC, format
 import { formatRoomCode } from "@/lib/roomCode";
 import { encodeFunctionData } from "viem";
 import { appendBuilderSuffix } from "@/lib/builderCode";
-import { useLobby } from "@/hooks/useSpacetimeDB";
+import { createLobbyAndWait, useLobby } from "@/hooks/useSpacetimeDB";
 import { useSpacetimeConnection } from "@/hooks/useSpacetimeDB";
 import { getDbConnection } from "@/lib/spacetimedb/client";
 import { useFarcaster } from "@/context/FarcasterProvider";
@@ -151,30 +151,30 @@ function LobbyContent() {
     return () => clearInterval(interval);
   }, [matchInfo]);
 
-  // Create SpacetimeDB lobby if it doesn't exist (for existing matches)
+  // Create SpacetimeDB lobby if it doesn't exist (for existing matches).
+  // The caller becomes the lobby's host, so only the on-chain host does this.
   useEffect(() => {
-    if (isSpacetimeConnected && match && !spacetimeLobby && matchIdParam && address) {
-      // Try to create lobby if match exists but SpacetimeDB lobby doesn't
+    const isMatchHost = !!address && match?.host?.toLowerCase() === address.toLowerCase();
+    if (isSpacetimeConnected && match && !spacetimeLobby && matchIdParam && isMatchHost) {
       const conn = getDbConnection();
       if (conn) {
-        try {
-          conn.reducers.createLobby({
-            chainId,
-            matchId: BigInt(matchId),
-            arenaAddress,
-            hostAddress: match.host,
-            entryFee: match.entryFee.toString(),
-            maxPlayers: match.maxPlayers,
-            hostName: match.host.slice(0, 6) + "..." + match.host.slice(-4),
-            lobbyId: roomCode,
-          });
-
-        } catch (err) {
+        createLobbyAndWait(conn, {
+          chainId,
+          matchId: BigInt(matchId),
+          arenaAddress,
+          entryFee: match.entryFee.toString(),
+          maxPlayers: match.maxPlayers,
+        }).then((created) => {
+          if (created) {
+            // Move to the room code the module assigned
+            router.replace(`/online/lobby/${created.roomCode}?host=true&chainId=${chainId}&arena=${arenaAddress}&matchId=${matchId}`);
+          }
+        }).catch((err) => {
           console.error('[LobbyPage] Failed to create lobby (may already exist):', err);
-        }
+        });
       }
     }
-  }, [isSpacetimeConnected, match, spacetimeLobby, matchIdParam, address, chainId, arenaAddress, roomCode, matchId]);
+  }, [isSpacetimeConnected, match, spacetimeLobby, matchIdParam, address, chainId, arenaAddress, matchId, router]);
 
   // Auto-confirm host deposit if needed (host pays on creation)
   const { confirmDeposit } = useLobby(roomCode);
@@ -183,10 +183,10 @@ function LobbyContent() {
       const hostPlayer = spacetimePlayers.find(p => p.address.toLowerCase() === spacetimeLobby.hostAddress.toLowerCase());
       if (hostPlayer && !hostPlayer.hasDeposited && hostPlayer.address.toLowerCase() === address.toLowerCase()) {
 
-        confirmDeposit(roomCode, address);
+        confirmDeposit(spacetimeLobby.id, address);
       }
     }
-  }, [isSpacetimeConnected, spacetimeLobby, address, spacetimePlayers, roomCode, confirmDeposit]);
+  }, [isSpacetimeConnected, spacetimeLobby, address, spacetimePlayers, confirmDeposit]);
 
   // Update players list - prefer SpacetimeDB data, fallback to contract
   useEffect(() => {
@@ -577,7 +577,7 @@ function LobbyContent() {
                   arenaAddress={arenaAddress}
                   matchId={matchId}
                   entryFee={entryFee}
-                  lobbyId={roomCode}
+                  lobbyId={spacetimeLobby?.id ?? roomCode}
                   onSuccess={handleJoinSuccess}
                   tokenAddress={match?.token}
                   tokenSymbol={tokenSymbol}
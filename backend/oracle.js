@@ -86,6 +86,21 @@ export async function finishMatch(chainId, matchId, winnerAddress) {
 // === SPACETIMEDB CONFIG ===
 const STDB_HOST = process.env.NEXT_PUBLIC_SPACETIMEDB_URI || "http://localhost:3000";
 const STDB_NAME = "onchain-reaction"; // Adjust if needed
+// Token for an identity registered with register_oracle; oracle-only reducers reject anyone else
+const STDB_TOKEN = process.env.STDB_ORACLE_TOKEN;
+
+// Sum-type columns (lobby status, options) come back as { "<Variant>": value }
+function variantName(value) {
+    if (typeof value === "string") return value;
+    return Object.keys(value ?? {})[0];
+}
+
+function optionValue(value) {
+    if (value && typeof value === "object" && ("some" in value || "none" in value)) {
+        return value.some ?? null;
+    }
+    return value;
+}
 
 // Helper to fetch table data
 async function fetchTable(tableName) {
@@ -109,7 +124,10 @@ async function callReducer(reducerName, args) {
     try {
         const res = await fetch(`${STDB_HOST}/database/call/${STDB_NAME}/${reducerName}`, {
             method: "POST",
-            headers: { "Content-Type": "application/json" },
+            headers: {
+                "Content-Type": "application/json",
+                ...(STDB_TOKEN ? { Authorization: `Bearer ${STDB_TOKEN}` } : {})
+            },
             body: JSON.stringify(args)
         });
         if (!res.ok) {
@@ -129,12 +147,12 @@ export async function pollMatches() {
     console.log("Polling matches...");
 
     try {
-        // Fetch live lobbies
+        // Fetch lobbies; status is an enum, so live/finished are picked out below
         // Added chain_id to query
         const lobbyRes = await fetch(`${STDB_HOST}/database/sql/${STDB_NAME}`, {
             method: "POST",
             headers: { "Content-Type": "text/plain" },
-            body: `SELECT id, match_id, status, winner_address, chain_id FROM lobby WHERE status = 'live' OR status = 'finished'`
+            body: `SELECT id, match_id, status, winner_address, chain_id FROM lobby`
         });
         const lobbyJson = await lobbyRes.json();
 
@@ -142,14 +160,15 @@ export async function pollMatches() {
         const lobbies = lobbyJson.data.map(row => ({
             id: row[0],
             matchId: row[1],
-            status: row[2],
-            winnerAddress: row[3],
+            status: variantName(row[2]),
+            winnerAddress: optionValue(row[3]),
             chainId: row[4]
-        }));
+        })).filter(lobby => lobby.status === 'Live' || lobby.status === 'Finished');
 
         for (const lobby of lobbies) {
             // Case 1: Game Finished -> Settle on Chain
-            if (lobby.status === 'finished' && lobby.winnerAddress) {
+            // Lobbies with payouts owed wait in Finished until record_match_finished
+            if (lobby.status === 'Finished' && lobby.winnerAddress) {
                 const client = clients[lobby.chainId];
                 if (!client) {
                     console.warn(`Skipping match ${lobby.matchId}: Unsupported chain ${lobby.chainId}`);
@@ -171,14 +190,16 @@ export async function pollMatches() {
                 // If match is Live (status=1), finalize it
                 if (matchStatus === 1) {
                     console.log(`Match ${lobby.matchId} finished in DB but still Live on chain ${lobby.chainId}. Settling...`);
-                    await finishMatch(lobby.chainId, lobby.matchId, lobby.winnerAddress);
-                } else {
-                    // console.log(`Match ${lobby.matchId} already settled (status=${matchStatus}).`);
+                    const { txHash } = await finishMatch(lobby.chainId, lobby.matchId, lobby.winnerAddress);
+                    await callReducer("record_match_finished", [lobby.id, txHash]);
+                } else if (matchStatus === 2 || matchStatus === 3) {
+                    // Finished on chain by an earlier poll whose reducer call didn't land
+                    await callReducer("record_match_finished", [lobby.id, ""]);
                 }
             }
 
             // Case 2: Game Live -> Check Timeout
-            if (lobby.status === 'live') {
+            if (lobby.status === 'Live') {
                 // Fetch GameState for this lobby
                 const gsRes = await fetch(`${STDB_HOST}/database/sql/${STDB_NAME}`, {
                     method: "POST",
@@ -6,12 +6,11 @@ import { onchainReactionAbi } from "@/lib/onchainReaction";
 import ERC20Abi from "@/abi/ERC20.json";
 import { ARENA_ADDRESSES, parseUSDC, ENTRY_FEE_OPTIONS, MAX_PLAYERS_OPTIONS, getChainName, CHAIN_IDS, USDC_ADDRESSES, TOKENS, parseTokenAmount, formatTokenAmount } from "@/lib/contracts";
 import { decodeEventLog, encodeFunctionData } from "viem";
-import { generateRoomCode, formatRoomCode } from "@/lib/roomCode";
+import { formatRoomCode } from "@/lib/roomCode";
 import { appendBuilderSuffix } from "@/lib/builderCode";
 import { motion, AnimatePresence } from "framer-motion";
-import { useSpacetimeConnection } from "@/hooks/useSpacetimeDB";
+import { createLobbyAndWait, useSpacetimeConnection } from "@/hooks/useSpacetimeDB";
 import { getDbConnection } from "@/lib/spacetimedb/client";
-import { useFarcaster } from "@/context/FarcasterProvider";
 
 interface CreateMatchButtonProps {
   onMatchCreated?: (matchId: number, chainId: number, roomCode?: string) => void;
@@ -21,7 +20,6 @@ export function CreateMatchButton({ onMatchCreated }: CreateMatchButtonProps) {
   const { address, chainId: connectedChainId } = useAccount();
   const publicClient = usePublicClient();
   const { isConnected: isSpacetimeConnected } = useSpacetimeConnection();
-  const { user: farcasterUser, isInMiniApp } = useFarcaster();
 
   const [selectedChain, setSelectedChain] = useState<number>(CHAIN_IDS.BASE);
   const [selectedToken, setSelectedToken] = useState<keyof typeof TOKENS>('USDC');
@@ -384,11 +382,10 @@ export function CreateMatchButton({ onMatchCreated }: CreateMatchButtonProps) {
           topics: matchCreatedLog.topics,
         });
         const matchId = Number((decoded.args as any).matchId);
-        const newRoomCode = generateRoomCode();
-        setRoomCode(newRoomCode);
         setCreatedMatchId(matchId);
 
-        // Create SpacetimeDB lobby
+        // Create SpacetimeDB lobby; the module assigns its room code
+        let newRoomCode = matchId.toString();
         if (isSpacetimeConnected && address) {
           try {
             const conn = getDbConnection();
@@ -396,38 +393,24 @@ export function CreateMatchButton({ onMatchCreated }: CreateMatchButtonProps) {
               const arenaAddress = ARENA_ADDRESSES[selectedChain];
               const entryFeeWei = parseTokenAmount(useCustomFee ? customEntryFee : entryFee, tokenConfig.decimals);
 
-              // Prepare host name with Farcaster data if available
-              let hostName: string;
-              if (isInMiniApp && farcasterUser) {
-                // Encode Farcaster data in JSON format (same as joiners)
-                hostName = JSON.stringify({
-                  displayName: farcasterUser.displayName || farcasterUser.username || `Player ${address.slice(0, 6)}`,
-                  username: farcasterUser.username,
-                  pfpUrl: farcasterUser.pfpUrl,
-                  fid: farcasterUser.fid
-                });
-              } else {
-                // Fallback to address-based name
-                hostName = address.slice(0, 6) + "..." + address.slice(-4);
-              }
-
-              conn.reducers.createLobby({
+              // Host name and address come from the caller's profile
+              const created = await createLobbyAndWait(conn, {
                 chainId: selectedChain,
                 matchId: BigInt(matchId),
                 arenaAddress,
-                hostAddress: address,
                 entryFee: entryFeeWei.toString(),
                 maxPlayers,
-                hostName,
-                lobbyId: newRoomCode,
               });
-
+              if (created) {
+                newRoomCode = created.roomCode;
+              }
             }
           } catch (err) {
             console.error('[CreateMatchButton] Failed to create SpacetimeDB lobby:', err);
             // Continue anyway - match is created on-chain
           }
         }
+        setRoomCode(newRoomCode);
 
         setShowRoomCode(true);
       }
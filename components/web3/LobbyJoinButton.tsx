@@ -7,10 +7,9 @@ import ERC20Abi from "@/abi/ERC20.json";
 import { USDC_ADDRESSES, parseUSDC, parseTokenAmount } from "@/lib/contracts";
 import { encodeFunctionData } from "viem";
 import { appendBuilderSuffix } from "@/lib/builderCode";
-import { useLobby } from "@/hooks/useSpacetimeDB";
+import { createLobbyAndWait, useLobby } from "@/hooks/useSpacetimeDB";
 import { useSpacetimeConnection } from "@/hooks/useSpacetimeDB";
 import { getDbConnection } from "@/lib/spacetimedb/client";
-import { useFarcaster } from "@/context/FarcasterProvider";
 
 interface LobbyJoinButtonProps {
   chainId: number;
@@ -40,7 +39,6 @@ export function LobbyJoinButton({
   const publicClient = usePublicClient();
   const { isConnected: isSpacetimeConnected } = useSpacetimeConnection();
   const { joinLobby, confirmDeposit } = useLobby(lobbyId || null);
-  const { user: farcasterUser, isInMiniApp } = useFarcaster();
   const [step, setStep] = useState<'check' | 'approve' | 'join' | 'done'>('check');
   const [txHash, setTxHash] = useState<`0x${string}` | undefined>();
   const [error, setError] = useState<string | null>(null);
@@ -112,55 +110,49 @@ export function LobbyJoinButton({
             if (isSpacetimeConnected) {
               // CRITICAL: Ensure lobby exists in SpacetimeDB before joining
               // After database clear, lobbies don't exist even if match is on-chain
+              let targetLobbyId = lobbyId;
               const conn = getDbConnection();
               if (conn) {
                 // Check if lobby exists
-                const existingLobby = conn.db.lobby.id.find(lobbyId);
-
-                if (!existingLobby && matchData) {
+                const existingLobby = conn.db.lobby.id.find(lobbyId) ?? conn.db.lobby.room_code.find(lobbyId.toUpperCase());
+
+                const [host, , entryFeeAmount, maxPlayers] = (matchData ?? []) as any;
+                // Creating the lobby seats the caller as its host, so only the on-chain host may do it
+                const isMatchHost = typeof host === 'string' && host.toLowerCase() === address.toLowerCase();
+
+                if (existingLobby) {
+                  targetLobbyId = existingLobby.id;
+                } else if (matchData && !isMatchHost) {
+                  // Joining below fails; the host reopens the lobby and we can join it then
+                  console.warn('[LobbyJoinButton] Lobby not found in SpacetimeDB; waiting for the host to open it');
+                } else if (matchData) {
                   console.log('[LobbyJoinButton] Lobby not found in SpacetimeDB, creating it first...');
-                  // Create the lobby first
+                  // Creating the lobby seats us as its host, with the deposit we just made
                   try {
-                    const [host, token, entryFeeAmount, maxPlayers] = matchData as any;
-                    conn.reducers.createLobby({
+                    const created = await createLobbyAndWait(conn, {
                       chainId,
                       matchId: BigInt(matchId),
                       arenaAddress,
-                      hostAddress: host,
                       entryFee: entryFeeAmount.toString(),
                       maxPlayers: Number(maxPlayers),
-                      hostName: `${host.slice(0, 6)}...${host.slice(-4)}`,
-                      lobbyId,
                     });
-                    // Wait a bit for lobby to be created
-                    await new Promise(r => setTimeout(r, 500));
+                    if (created) {
+                      setStep('done');
+                      onSuccess?.();
+                      return;
+                    }
                   } catch (createErr) {
                     console.log('[LobbyJoinButton] Lobby creation failed (may already exist):', createErr);
                   }
                 }
               }
 
-              // Prepare player name with Farcaster data if available
-              let playerName: string;
-              if (isInMiniApp && farcasterUser) {
-                // Encode Farcaster data in JSON format
-                playerName = JSON.stringify({
-                  displayName: farcasterUser.displayName || farcasterUser.username || `Player ${address.slice(0, 6)}`,
-                  username: farcasterUser.username,
-                  pfpUrl: farcasterUser.pfpUrl,
-                  fid: farcasterUser.fid
-                });
-              } else {
-                // Fallback to address-based name
-                playerName = `Player ${address.slice(0, 6)}...${address.slice(-4)}`;
-              }
-
-              // Now join SpacetimeDB lobby
-              const joined = await joinLobby(lobbyId, address, playerName);
+              // Now join SpacetimeDB lobby (name and address come from our profile)
+              const joined = await joinLobby(targetLobbyId);
               if (joined) {
 
                 // Confirm deposit
-                await confirmDeposit(lobbyId, address);
+                await confirmDeposit(targetLobbyId, address);
 
               } else {
                 console.warn('[LobbyJoinButton] Failed to join SpacetimeDB lobby, but player is on-chain');
@@ -175,7 +167,7 @@ export function LobbyJoinButton({
         })();
       }
     }
-  }, [isSuccess, txHash, step, refetchAllowance, onSuccess, address, lobbyId, isSpacetimeConnected, joinLobby, confirmDeposit, isInMiniApp, farcasterUser]);
+  }, [isSuccess, txHash, step, refetchAllowance, onSuccess, address, lobbyId, isSpacetimeConnected, joinLobby, confirmDeposit]);
 
   async function handleApprove() {
     setError(null);
@@ -12,7 +12,11 @@ import {
   type EventContext,
   type ReducerEventContext,
 } from "@/lib/spacetimedb/client";
-import { DbConnection } from "@/lib/spacetimedb/generated";
+import { DbConnection, LobbyRow } from "@/lib/spacetimedb/generated";
+import type { Infer } from "spacetimedb";
+
+// The module's LobbyStatus enum, lowercased by toLobby
+export type LobbyStatus = "waiting" | "live" | "finished" | "settling" | "settled" | "cancelled";
 
 // Types from generated code
 export type Lobby = {
@@ -24,7 +28,7 @@ export type Lobby = {
   hostAddress: string;
   entryFee: string;
   maxPlayers: number;
-  status: string;
+  status: LobbyStatus;
   winnerIdentity: Identity | null;
   winnerAddress: string | null;
   createdAt: bigint;
@@ -70,6 +74,18 @@ export type GameMove = {
 
 export type Board = { orbs: number; owner: string | null }[][];
 
+function toLobby(row: Infer<typeof LobbyRow>): Lobby {
+  return {
+    ...(row as unknown as Lobby),
+    status: row.status.tag.toLowerCase() as LobbyStatus,
+  };
+}
+
+/** Whether the lobby's game has been played to a result (payouts may still be pending) */
+export function hasResult(status: LobbyStatus | undefined): boolean {
+  return status === "finished" || status === "settling" || status === "settled";
+}
+
 export function parseBoard(boardJson: string): Board {
   try {
     const parsed = JSON.parse(boardJson);
@@ -87,6 +103,117 @@ export function parseBoard(boardJson: string): Board {
 
 export const PLAYER_COLORS = ["red", "blue", "green", "yellow", "purple", "orange", "pink", "cyan"];
 
+export type CreatedLobby = { lobbyId: string; roomCode: string };
+
+// How long to wait for the module to publish a lobby we created
+const CREATE_LOBBY_TIMEOUT_MS = 10_000;
+
+/**
+ * Call create_lobby and resolve with the id and room code the module
+ * assigned, read back from the caller's last_created_lobby row. Resolves
+ * null if the reducer fails or nothing arrives in time.
+ */
+export function createLobbyAndWait(
+  conn: DbConnection,
+  args: { chainId: number; matchId: bigint; arenaAddress: string; entryFee: string; maxPlayers: number }
+): Promise<CreatedLobby | null> {
+  const me = conn.identity;
+  if (!me) return Promise.resolve(null);
+
+  return new Promise((resolve) => {
+    let finished = false;
+    const finish = (created: CreatedLobby | null) => {
+      if (finished) return;
+      finished = true;
+      clearTimeout(timeout);
+      conn.db.lastCreatedLobby.removeOnInsert(onInsert);
+      conn.db.lastCreatedLobby.removeOnUpdate(onUpdate);
+      conn.reducers.removeOnCreateLobby(onReducer);
+      subscription.unsubscribe();
+      resolve(created);
+    };
+
+    const onRow = (row: { identity: Identity; lobbyId: string; roomCode: string }) => {
+      if (row.identity.isEqual(me)) {
+        finish({ lobbyId: row.lobbyId, roomCode: row.roomCode });
+      }
+    };
+    const onInsert = (_ctx: EventContext, row: Parameters<typeof onRow>[0]) => onRow(row);
+    const onUpdate = (_ctx: EventContext, _old: Parameters<typeof onRow>[0], row: Parameters<typeof onRow>[0]) => onRow(row);
+    const onReducer = (ctx: ReducerEventContext) => {
+      if (ctx.event.callerIdentity.isEqual(me) && ctx.event.status.tag !== "Committed") {
+        console.error("create_lobby failed:", ctx.event.status);
+        finish(null);
+      }
+    };
+
+    const timeout = setTimeout(() => finish(null), CREATE_LOBBY_TIMEOUT_MS);
+
+    // Listen only once our current row (if any) has arrived, so it isn't mistaken for the new lobby
+    const subscription = conn.subscriptionBuilder()
+      .onApplied(() => {
+        conn.db.lastCreatedLobby.onInsert(onInsert);
+        conn.db.lastCreatedLobby.onUpdate(onUpdate);
+        conn.reducers.onCreateLobby(onReducer);
+        conn.reducers.createLobby({
+          ...args,
+          timeControl: { tag: "Standard" },
+          visibility: { tag: "Public" },
+          passcode: undefined,
+        });
+      })
+      .onError((err) => {
+        console.error("last_created_lobby subscription error:", err);
+        finish(null);
+      })
+      .subscribe([`SELECT * FROM last_created_lobby WHERE identity = 0x${me.toHexString()}`]);
+  });
+}
+
+// Lobby ids are hex, room codes use A-Z/2-9 and fallback ids are match ids, so anything
+// else (quotes included) never reaches the SQL below
+const LOBBY_KEY_PATTERN = /^[0-9A-Za-z]{1,64}$/;
+
+/**
+ * Resolve a lobby id or room code (as shared or typed in) to the lobby's id
+ */
+function useLobbyId(idOrCode: string | null, isConnected: boolean): string | null {
+  const [lobbyId, setLobbyId] = useState<string | null>(null);
+
+  useEffect(() => {
+    setLobbyId(null);
+    if (!isConnected || !idOrCode) return;
+    if (!LOBBY_KEY_PATTERN.test(idOrCode)) {
+      console.error("Invalid lobby id or room code:", idOrCode);
+      return;
+    }
+
+    const conn = getDbConnection();
+    if (!conn) return;
+
+    const roomCode = idOrCode.toUpperCase();
+    const subscription = conn.subscriptionBuilder()
+      .onApplied((ctx) => {
+        const found = ctx.db.lobby.id.find(idOrCode) ?? ctx.db.lobby.room_code.find(roomCode);
+        setLobbyId(found?.id ?? idOrCode);
+      })
+      .onError((err) => {
+        console.error("Lobby lookup error:", err);
+        setLobbyId(idOrCode);
+      })
+      .subscribe([
+        `SELECT * FROM lobby WHERE id = '${idOrCode}'`,
+        `SELECT * FROM lobby WHERE room_code = '${roomCode}'`,
+      ]);
+
+    return () => {
+      subscription?.unsubscribe();
+    };
+  }, [isConnected, idOrCode]);
+
+  return lobbyId;
+}
+
 // Connection status
 export type ConnectionStatus = "disconnected" | "connecting" | "connected" | "error";
 
@@ -146,9 +273,9 @@ export function useSpacetimeConnection() {
 }
 
 /**
- * Hook for lobby state and actions
+ * Hook for lobby state and actions, for a lobby given by id or room code
  */
-export function useLobby(lobbyId: string | null) {
+export function useLobby(idOrCode: string | null) {
   const [lobby, setLobby] = useState<Lobby | null>(null);
   const [players, setPlayers] = useState<LobbyPlayer[]>([]);
   const [gameState, setGameState] = useState<GameState | null>(null);
@@ -157,6 +284,7 @@ export function useLobby(lobbyId: string | null) {
   const [isLoading, setIsLoading] = useState(true);
 
   const { connection, isConnected, identity } = useSpacetimeConnection();
+  const lobbyId = useLobbyId(idOrCode, isConnected);
 
   // Subscribe to tables and set up event handlers
   useEffect(() => {
@@ -178,7 +306,7 @@ export function useLobby(lobbyId: string | null) {
         // Load initial data
         const lobbyData = ctx.db.lobby.id.find(lobbyId);
         if (lobbyData) {
-          setLobby(lobbyData as unknown as Lobby);
+          setLobby(toLobby(lobbyData));
         }
 
         const playersData = Array.from(ctx.db.lobbyPlayer.lobby_id.filter(lobbyId));
@@ -236,18 +364,18 @@ export function useLobby(lobbyId: string | null) {
     // Set up table event handlers
     conn.db.lobby.onInsert((ctx, row) => {
       if (row.id === lobbyId) {
-        setLobby(row as unknown as Lobby);
+        setLobby(toLobby(row));
       }
     });
 
     conn.db.lobby.onUpdate((ctx, oldRow, newRow) => {
       if (newRow.id === lobbyId) {
         console.log(`[useLobby] Lobby status updated:`, {
-          oldStatus: oldRow.status,
-          newStatus: newRow.status,
+          oldStatus: oldRow.status.tag,
+          newStatus: newRow.status.tag,
           lobbyId: newRow.id
         });
-        setLobby(newRow as unknown as Lobby);
+        setLobby(toLobby(newRow));
       }
     });
 
@@ -352,53 +480,38 @@ export function useLobby(lobbyId: string | null) {
   }, [isConnected, lobbyId]);
 
   // Actions
+  // The host's address and name come from their profile
   const createLobby = useCallback(
     async (
       chainId: number,
       matchId: bigint,
       arenaAddress: string,
-      hostAddress: string,
       entryFee: string,
-      maxPlayers: number,
-      hostName: string
-    ): Promise<string | null> => {
+      maxPlayers: number
+    ): Promise<CreatedLobby | null> => {
       const conn = getDbConnection();
-      if (!conn || !lobbyId) return null;
+      if (!conn) return null;
 
       try {
-        conn.reducers.createLobby({
-          chainId,
-          matchId,
-          arenaAddress,
-          hostAddress,
-          entryFee,
-          maxPlayers,
-          hostName,
-          lobbyId,
-        });
-        return lobbyId;
+        return await createLobbyAndWait(conn, { chainId, matchId, arenaAddress, entryFee, maxPlayers });
       } catch (err) {
         console.error("Failed to create lobby:", err);
         return null;
       }
     },
-    [lobbyId]
+    []
   );
 
+  // The player's address and name come from their profile
   const joinLobby = useCallback(
-    async (
-      targetLobbyId: string,
-      playerAddress: string,
-      playerName: string
-    ): Promise<boolean> => {
+    async (targetLobbyId: string, passcode?: string): Promise<boolean> => {
       const conn = getDbConnection();
       if (!conn) return false;
 
       try {
         conn.reducers.joinLobby({
           lobbyId: targetLobbyId,
-          playerAddress,
-          playerName,
+          passcode,
         });
         return true;
       } catch (err) {
@@ -564,35 +677,36 @@ export function useLobbyList() {
     // Subscribe to all lobbies
     const subscription = conn.subscriptionBuilder()
       .onApplied((ctx) => {
-        const allLobbies = Array.from(ctx.db.lobby.iter());
-        setLobbies(allLobbies as unknown as Lobby[]);
+        const allLobbies = Array.from(ctx.db.lobby.iter()).map(toLobby);
+        setLobbies(allLobbies.filter(l => l.status === "waiting"));
         setIsLoading(false);
       })
       .onError((err) => {
         console.error("Lobby list subscription error:", err);
         setIsLoading(false);
       })
-      .subscribe(["SELECT * FROM lobby WHERE status = 'waiting'"]);
+      // status is an enum, so waiting lobbies are filtered client-side
+      .subscribe(["SELECT * FROM lobby"]);
 
     // Set up event handlers
     conn.db.lobby.onInsert((ctx, row) => {
-      if (row.status === "waiting") {
+      if (row.status.tag === "Waiting") {
         setLobbies(prev => {
           const existing = prev.find(l => l.id === row.id);
           if (existing) return prev;
-          return [...prev, row as unknown as Lobby];
+          return [...prev, toLobby(row)];
         });
       }
     });
 
     conn.db.lobby.onUpdate((ctx, oldRow, newRow) => {
       setLobbies(prev => {
-        if (newRow.status === "waiting") {
+        if (newRow.status.tag === "Waiting") {
           const existing = prev.find(l => l.id === newRow.id);
           if (existing) {
-            return prev.map(l => l.id === newRow.id ? newRow as unknown as Lobby : l);
+            return prev.map(l => l.id === newRow.id ? toLobby(newRow) : l);
           }
-          return [...prev, newRow as unknown as Lobby];
+          return [...prev, toLobby(newRow)];
         } else {
           return prev.filter(l => l.id !== newRow.id);
         }
@@ -13,6 +13,7 @@ import {
   LobbyPlayer,
   GameState,
   GameMove,
+  ErrorCode,
 } from "./generated";
 
 export const SPACETIMEDB_CONFIG = {
@@ -24,7 +25,7 @@ let dbConnection: DbConnection | null = null;
 let connectionPromise: Promise<DbConnection> | null = null;
 
 export type { EventContext, ReducerEventContext, SubscriptionEventContext, ErrorContext };
-export { tables, reducers, Lobby, LobbyPlayer, GameState, GameMove };
+export { tables, reducers, Lobby, LobbyPlayer, GameState, GameMove, ErrorCode };
 
 export function getDbConnection(): DbConnection | null {
   return dbConnection;
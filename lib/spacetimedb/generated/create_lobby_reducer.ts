@@ -9,14 +9,20 @@ import {
   type AlgebraicTypeType as __AlgebraicTypeType,
   type Infer as __Infer,
 } from "spacetimedb";
+import LobbyVisibility from "./lobby_visibility_type";
+import TimeControl from "./time_control_type";
 
 export default {
   chainId: __t.u32(),
   matchId: __t.u64(),
   arenaAddress: __t.string(),
-  hostAddress: __t.string(),
   entryFee: __t.string(),
   maxPlayers: __t.u32(),
-  hostName: __t.string(),
-  lobbyId: __t.string(),
+  get timeControl() {
+    return TimeControl;
+  },
+  get visibility() {
+    return LobbyVisibility;
+  },
+  passcode: __t.option(__t.string()),
 };
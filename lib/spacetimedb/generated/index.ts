@@ -31,51 +31,379 @@ import {
 } from "spacetimedb";
 
 // Import and reexport all reducer arg types
+import AcceptRematch from "./accept_rematch_reducer";
+export { AcceptRematch };
+import AddModerator from "./add_moderator_reducer";
+export { AddModerator };
+import AttestFarcaster from "./attest_farcaster_reducer";
+export { AttestFarcaster };
+import CancelTournament from "./cancel_tournament_reducer";
+export { CancelTournament };
+import ChooseColor from "./choose_color_reducer";
+export { ChooseColor };
+import ClaimRoundTimeout from "./claim_round_timeout_reducer";
+export { ClaimRoundTimeout };
 import ClaimTimeout from "./claim_timeout_reducer";
 export { ClaimTimeout };
 import ConfirmDeposit from "./confirm_deposit_reducer";
 export { ConfirmDeposit };
 import CreateLobby from "./create_lobby_reducer";
 export { CreateLobby };
+import CreateTournament from "./create_tournament_reducer";
+export { CreateTournament };
+import DeleteChatMessage from "./delete_chat_message_reducer";
+export { DeleteChatMessage };
+import EnterQueue from "./enter_queue_reducer";
+export { EnterQueue };
+import ExpireMatchedLobby from "./expire_matched_lobby_reducer";
+export { ExpireMatchedLobby };
 import IdentityConnected from "./identity_connected_reducer";
 export { IdentityConnected };
 import IdentityDisconnected from "./identity_disconnected_reducer";
 export { IdentityDisconnected };
+import InviteToLobby from "./invite_to_lobby_reducer";
+export { InviteToLobby };
+import JoinAsSpectator from "./join_as_spectator_reducer";
+export { JoinAsSpectator };
 import JoinLobby from "./join_lobby_reducer";
 export { JoinLobby };
+import KickPlayer from "./kick_player_reducer";
+export { KickPlayer };
 import LeaveLobby from "./leave_lobby_reducer";
 export { LeaveLobby };
+import LeaveQueue from "./leave_queue_reducer";
+export { LeaveQueue };
+import LeaveSpectating from "./leave_spectating_reducer";
+export { LeaveSpectating };
+import LinkFarcaster from "./link_farcaster_reducer";
+export { LinkFarcaster };
+import LinkWallet from "./link_wallet_reducer";
+export { LinkWallet };
+import LockLobby from "./lock_lobby_reducer";
+export { LockLobby };
 import MakeMove from "./make_move_reducer";
 export { MakeMove };
+import MarkRefundProcessed from "./mark_refund_processed_reducer";
+export { MarkRefundProcessed };
+import MarkSettlementExecuted from "./mark_settlement_executed_reducer";
+export { MarkSettlementExecuted };
+import MuteUser from "./mute_user_reducer";
+export { MuteUser };
 import Ping from "./ping_reducer";
 export { Ping };
+import PruneAuditEvents from "./prune_audit_events_reducer";
+export { PruneAuditEvents };
+import RecordMatchFinished from "./record_match_finished_reducer";
+export { RecordMatchFinished };
+import RegisterForTournament from "./register_for_tournament_reducer";
+export { RegisterForTournament };
+import RegisterOracle from "./register_oracle_reducer";
+export { RegisterOracle };
+import RemoveModerator from "./remove_moderator_reducer";
+export { RemoveModerator };
+import RemoveOracle from "./remove_oracle_reducer";
+export { RemoveOracle };
+import RequestRematch from "./request_rematch_reducer";
+export { RequestRematch };
+import RequestWalletNonce from "./request_wallet_nonce_reducer";
+export { RequestWalletNonce };
+import Resign from "./resign_reducer";
+export { Resign };
+import RevokeInvite from "./revoke_invite_reducer";
+export { RevokeInvite };
+import RunMatchmaker from "./run_matchmaker_reducer";
+export { RunMatchmaker };
+import SeedCatalogues from "./seed_catalogues_reducer";
+export { SeedCatalogues };
+import SendChat from "./send_chat_reducer";
+export { SendChat };
+import SetFarcasterAccount from "./set_farcaster_account_reducer";
+export { SetFarcasterAccount };
+import SetLobbyVisibility from "./set_lobby_visibility_reducer";
+export { SetLobbyVisibility };
+import SetMatchId from "./set_match_id_reducer";
+export { SetMatchId };
+import SetPreferredColor from "./set_preferred_color_reducer";
+export { SetPreferredColor };
+import SetProfile from "./set_profile_reducer";
+export { SetProfile };
+import SetReady from "./set_ready_reducer";
+export { SetReady };
+import SetSpectatorPolicy from "./set_spectator_policy_reducer";
+export { SetSpectatorPolicy };
 import StartGame from "./start_game_reducer";
 export { StartGame };
+import StartNewSeason from "./start_new_season_reducer";
+export { StartNewSeason };
+import StartTournament from "./start_tournament_reducer";
+export { StartTournament };
+import TransferHost from "./transfer_host_reducer";
+export { TransferHost };
+import UnlinkFarcaster from "./unlink_farcaster_reducer";
+export { UnlinkFarcaster };
+import UnmuteUser from "./unmute_user_reducer";
+export { UnmuteUser };
+import WithdrawFromTournament from "./withdraw_from_tournament_reducer";
+export { WithdrawFromTournament };
 
 // Import and reexport all procedure arg types
 
 // Import and reexport all table handle types
+import AchievementRow from "./achievement_table";
+export { AchievementRow };
+import AuditEventRow from "./audit_event_table";
+export { AuditEventRow };
+import ChatMessageRow from "./chat_message_table";
+export { ChatMessageRow };
+import ChatMuteRow from "./chat_mute_table";
+export { ChatMuteRow };
+import ErrorCodeRow from "./error_code_table";
+export { ErrorCodeRow };
+import FarcasterAccountRow from "./farcaster_account_table";
+export { FarcasterAccountRow };
+import FarcasterLinkRow from "./farcaster_link_table";
+export { FarcasterLinkRow };
 import GameMoveRow from "./game_move_table";
 export { GameMoveRow };
 import GameStateRow from "./game_state_table";
 export { GameStateRow };
+import LastCreatedLobbyRow from "./last_created_lobby_table";
+export { LastCreatedLobbyRow };
+import LeaderboardEntryRow from "./leaderboard_entry_table";
+export { LeaderboardEntryRow };
 import LobbyRow from "./lobby_table";
 export { LobbyRow };
+import LobbyInviteRow from "./lobby_invite_table";
+export { LobbyInviteRow };
 import LobbyPlayerRow from "./lobby_player_table";
 export { LobbyPlayerRow };
+import LobbyTransitionRow from "./lobby_transition_table";
+export { LobbyTransitionRow };
+import MatchStatsRow from "./match_stats_table";
+export { MatchStatsRow };
+import MatchmakingTicketRow from "./matchmaking_ticket_table";
+export { MatchmakingTicketRow };
+import ModeratorRow from "./moderator_table";
+export { ModeratorRow };
+import OracleRow from "./oracle_table";
+export { OracleRow };
+import PlayerAchievementRow from "./player_achievement_table";
+export { PlayerAchievementRow };
+import PlayerRatingRow from "./player_rating_table";
+export { PlayerRatingRow };
+import ProfileRow from "./profile_table";
+export { ProfileRow };
+import RefundRow from "./refund_table";
+export { RefundRow };
+import SeasonRow from "./season_table";
+export { SeasonRow };
+import SeasonArchiveRow from "./season_archive_table";
+export { SeasonArchiveRow };
+import SettlementRow from "./settlement_table";
+export { SettlementRow };
+import SpectatorRow from "./spectator_table";
+export { SpectatorRow };
+import StandingRow from "./standing_table";
+export { StandingRow };
+import TournamentRow from "./tournament_table";
+export { TournamentRow };
+import TournamentEntryRow from "./tournament_entry_table";
+export { TournamentEntryRow };
+import TournamentMatchRow from "./tournament_match_table";
+export { TournamentMatchRow };
+import WalletLinkRow from "./wallet_link_table";
+export { WalletLinkRow };
+import WalletNonceRow from "./wallet_nonce_table";
+export { WalletNonceRow };
 
 // Import and reexport all types
+import Achievement from "./achievement_type";
+export { Achievement };
+import AchievementTracker from "./achievement_tracker_type";
+export { AchievementTracker };
+import AuditEvent from "./audit_event_type";
+export { AuditEvent };
+import AuditPruneSchedule from "./audit_prune_schedule_type";
+export { AuditPruneSchedule };
+import ChatMessage from "./chat_message_type";
+export { ChatMessage };
+import ChatMute from "./chat_mute_type";
+export { ChatMute };
+import ChatRateLimit from "./chat_rate_limit_type";
+export { ChatRateLimit };
+import ClockSettings from "./clock_settings_type";
+export { ClockSettings };
+import DepositDeadline from "./deposit_deadline_type";
+export { DepositDeadline };
+import ErrorCode from "./error_code_type";
+export { ErrorCode };
+import ErrorCodeInfo from "./error_code_info_type";
+export { ErrorCodeInfo };
+import FarcasterAccount from "./farcaster_account_type";
+export { FarcasterAccount };
+import FarcasterLink from "./farcaster_link_type";
+export { FarcasterLink };
 import GameMove from "./game_move_type";
 export { GameMove };
 import GameState from "./game_state_type";
 export { GameState };
+import LastCreatedLobby from "./last_created_lobby_type";
+export { LastCreatedLobby };
+import LeaderboardEntry from "./leaderboard_entry_type";
+export { LeaderboardEntry };
 import Lobby from "./lobby_type";
 export { Lobby };
+import LobbyInvite from "./lobby_invite_type";
+export { LobbyInvite };
+import LobbyPasscode from "./lobby_passcode_type";
+export { LobbyPasscode };
 import LobbyPlayer from "./lobby_player_type";
 export { LobbyPlayer };
+import LobbyStatus from "./lobby_status_type";
+export { LobbyStatus };
+import LobbyTransition from "./lobby_transition_type";
+export { LobbyTransition };
+import LobbyVisibility from "./lobby_visibility_type";
+export { LobbyVisibility };
+import MatchStats from "./match_stats_type";
+export { MatchStats };
+import MatchmakingSchedule from "./matchmaking_schedule_type";
+export { MatchmakingSchedule };
+import MatchmakingTicket from "./matchmaking_ticket_type";
+export { MatchmakingTicket };
+import Moderator from "./moderator_type";
+export { Moderator };
+import Oracle from "./oracle_type";
+export { Oracle };
+import PlayerAchievement from "./player_achievement_type";
+export { PlayerAchievement };
+import PlayerRating from "./player_rating_type";
+export { PlayerRating };
+import Profile from "./profile_type";
+export { Profile };
+import Refund from "./refund_type";
+export { Refund };
+import Season from "./season_type";
+export { Season };
+import SeasonArchive from "./season_archive_type";
+export { SeasonArchive };
+import Settlement from "./settlement_type";
+export { Settlement };
+import Spectator from "./spectator_type";
+export { Spectator };
+import Standing from "./standing_type";
+export { Standing };
+import SwissSettings from "./swiss_settings_type";
+export { SwissSettings };
+import Tiebreak from "./tiebreak_type";
+export { Tiebreak };
+import TimeControl from "./time_control_type";
+export { TimeControl };
+import Tournament from "./tournament_type";
+export { Tournament };
+import TournamentEntry from "./tournament_entry_type";
+export { TournamentEntry };
+import TournamentFormat from "./tournament_format_type";
+export { TournamentFormat };
+import TournamentMatch from "./tournament_match_type";
+export { TournamentMatch };
+import WalletLink from "./wallet_link_type";
+export { WalletLink };
+import WalletNonce from "./wallet_nonce_type";
+export { WalletNonce };
 
 /** The schema information for all tables in this module. This is defined the same was as the tables would have been defined in the server. */
 const tablesSchema = __schema(
+  __table({
+    name: 'achievement',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'achievement_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, AchievementRow),
+  __table({
+    name: 'audit_event',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'timestamp_micros', algorithm: 'btree', columns: [
+        'timestampMicros',
+      ] },
+      { name: 'sender', algorithm: 'btree', columns: [
+        'sender',
+      ] },
+    ],
+    constraints: [
+      { name: 'audit_event_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, AuditEventRow),
+  __table({
+    name: 'chat_message',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'chat_message_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, ChatMessageRow),
+  __table({
+    name: 'chat_mute',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'chat_mute_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, ChatMuteRow),
+  __table({
+    name: 'error_code',
+    indexes: [
+      { name: 'code', algorithm: 'btree', columns: [
+        'code',
+      ] },
+    ],
+    constraints: [
+      { name: 'error_code_code_key', constraint: 'unique', columns: ['code'] },
+    ],
+  }, ErrorCodeRow),
+  __table({
+    name: 'farcaster_account',
+    indexes: [
+      { name: 'fid', algorithm: 'btree', columns: [
+        'fid',
+      ] },
+    ],
+    constraints: [
+      { name: 'farcaster_account_fid_key', constraint: 'unique', columns: ['fid'] },
+    ],
+  }, FarcasterAccountRow),
+  __table({
+    name: 'farcaster_link',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+      { name: 'fid', algorithm: 'btree', columns: [
+        'fid',
+      ] },
+    ],
+    constraints: [
+      { name: 'farcaster_link_identity_key', constraint: 'unique', columns: ['identity'] },
+      { name: 'farcaster_link_fid_key', constraint: 'unique', columns: ['fid'] },
+    ],
+  }, FarcasterLinkRow),
   __table({
     name: 'game_move',
     indexes: [
@@ -101,17 +429,63 @@ const tablesSchema = __schema(
       { name: 'game_state_lobby_id_key', constraint: 'unique', columns: ['lobbyId'] },
     ],
   }, GameStateRow),
+  __table({
+    name: 'last_created_lobby',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'last_created_lobby_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, LastCreatedLobbyRow),
+  __table({
+    name: 'leaderboard_entry',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'board', algorithm: 'btree', columns: [
+        'seasonId',
+        'chainId',
+        'maxPlayers',
+        'variant',
+      ] },
+    ],
+    constraints: [
+      { name: 'leaderboard_entry_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, LeaderboardEntryRow),
   __table({
     name: 'lobby',
     indexes: [
       { name: 'id', algorithm: 'btree', columns: [
         'id',
       ] },
+      { name: 'room_code', algorithm: 'btree', columns: [
+        'roomCode',
+      ] },
     ],
     constraints: [
       { name: 'lobby_id_key', constraint: 'unique', columns: ['id'] },
+      { name: 'lobby_room_code_key', constraint: 'unique', columns: ['roomCode'] },
     ],
   }, LobbyRow),
+  __table({
+    name: 'lobby_invite',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'lobby_invite_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, LobbyInviteRow),
   __table({
     name: 'lobby_player',
     indexes: [
@@ -126,18 +500,316 @@ const tablesSchema = __schema(
       { name: 'lobby_player_id_key', constraint: 'unique', columns: ['id'] },
     ],
   }, LobbyPlayerRow),
+  __table({
+    name: 'lobby_transition',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'lobby_transition_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, LobbyTransitionRow),
+  __table({
+    name: 'match_stats',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'match_stats_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, MatchStatsRow),
+  __table({
+    name: 'matchmaking_ticket',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'matchmaking_ticket_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, MatchmakingTicketRow),
+  __table({
+    name: 'moderator',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'moderator_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, ModeratorRow),
+  __table({
+    name: 'oracle',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'oracle_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, OracleRow),
+  __table({
+    name: 'player_achievement',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'player_achievement_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, PlayerAchievementRow),
+  __table({
+    name: 'player_rating',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'player_key', algorithm: 'btree', columns: [
+        'playerKey',
+      ] },
+    ],
+    constraints: [
+      { name: 'player_rating_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, PlayerRatingRow),
+  __table({
+    name: 'profile',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+      { name: 'primary_address', algorithm: 'btree', columns: [
+        'primaryAddress',
+      ] },
+    ],
+    constraints: [
+      { name: 'profile_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, ProfileRow),
+  __table({
+    name: 'refund',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'refund_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, RefundRow),
+  __table({
+    name: 'season',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'season_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, SeasonRow),
+  __table({
+    name: 'season_archive',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'season_id', algorithm: 'btree', columns: [
+        'seasonId',
+      ] },
+    ],
+    constraints: [
+      { name: 'season_archive_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, SeasonArchiveRow),
+  __table({
+    name: 'settlement',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'settlement_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, SettlementRow),
+  __table({
+    name: 'spectator',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'spectator_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, SpectatorRow),
+  __table({
+    name: 'standing',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'lobby_id', algorithm: 'btree', columns: [
+        'lobbyId',
+      ] },
+    ],
+    constraints: [
+      { name: 'standing_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, StandingRow),
+  __table({
+    name: 'tournament',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'tournament_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, TournamentRow),
+  __table({
+    name: 'tournament_entry',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'tournament_id', algorithm: 'btree', columns: [
+        'tournamentId',
+      ] },
+    ],
+    constraints: [
+      { name: 'tournament_entry_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, TournamentEntryRow),
+  __table({
+    name: 'tournament_match',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'tournament_id', algorithm: 'btree', columns: [
+        'tournamentId',
+      ] },
+    ],
+    constraints: [
+      { name: 'tournament_match_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, TournamentMatchRow),
+  __table({
+    name: 'wallet_link',
+    indexes: [
+      { name: 'address', algorithm: 'btree', columns: [
+        'address',
+      ] },
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'wallet_link_address_key', constraint: 'unique', columns: ['address'] },
+    ],
+  }, WalletLinkRow),
+  __table({
+    name: 'wallet_nonce',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'wallet_nonce_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, WalletNonceRow),
 );
 
 /** The schema information for all reducers in this module. This is defined the same way as the reducers would have been defined in the server, except the body of the reducer is omitted in code generation. */
 const reducersSchema = __reducers(
+  __reducerSchema("accept_rematch", AcceptRematch),
+  __reducerSchema("add_moderator", AddModerator),
+  __reducerSchema("attest_farcaster", AttestFarcaster),
+  __reducerSchema("cancel_tournament", CancelTournament),
+  __reducerSchema("choose_color", ChooseColor),
+  __reducerSchema("claim_round_timeout", ClaimRoundTimeout),
   __reducerSchema("claim_timeout", ClaimTimeout),
   __reducerSchema("confirm_deposit", ConfirmDeposit),
   __reducerSchema("create_lobby", CreateLobby),
+  __reducerSchema("create_tournament", CreateTournament),
+  __reducerSchema("delete_chat_message", DeleteChatMessage),
+  __reducerSchema("enter_queue", EnterQueue),
+  __reducerSchema("expire_matched_lobby", ExpireMatchedLobby),
+  __reducerSchema("invite_to_lobby", InviteToLobby),
+  __reducerSchema("join_as_spectator", JoinAsSpectator),
   __reducerSchema("join_lobby", JoinLobby),
+  __reducerSchema("kick_player", KickPlayer),
   __reducerSchema("leave_lobby", LeaveLobby),
+  __reducerSchema("leave_queue", LeaveQueue),
+  __reducerSchema("leave_spectating", LeaveSpectating),
+  __reducerSchema("link_farcaster", LinkFarcaster),
+  __reducerSchema("link_wallet", LinkWallet),
+  __reducerSchema("lock_lobby", LockLobby),
   __reducerSchema("make_move", MakeMove),
+  __reducerSchema("mark_refund_processed", MarkRefundProcessed),
+  __reducerSchema("mark_settlement_executed", MarkSettlementExecuted),
+  __reducerSchema("mute_user", MuteUser),
   __reducerSchema("ping", Ping),
+  __reducerSchema("prune_audit_events", PruneAuditEvents),
+  __reducerSchema("record_match_finished", RecordMatchFinished),
+  __reducerSchema("register_for_tournament", RegisterForTournament),
+  __reducerSchema("register_oracle", RegisterOracle),
+  __reducerSchema("remove_moderator", RemoveModerator),
+  __reducerSchema("remove_oracle", RemoveOracle),
+  __reducerSchema("request_rematch", RequestRematch),
+  __reducerSchema("request_wallet_nonce", RequestWalletNonce),
+  __reducerSchema("resign", Resign),
+  __reducerSchema("revoke_invite", RevokeInvite),
+  __reducerSchema("run_matchmaker", RunMatchmaker),
+  __reducerSchema("seed_catalogues", SeedCatalogues),
+  __reducerSchema("send_chat", SendChat),
+  __reducerSchema("set_farcaster_account", SetFarcasterAccount),
+  __reducerSchema("set_lobby_visibility", SetLobbyVisibility),
+  __reducerSchema("set_match_id", SetMatchId),
+  __reducerSchema("set_preferred_color", SetPreferredColor),
+  __reducerSchema("set_profile", SetProfile),
+  __reducerSchema("set_ready", SetReady),
+  __reducerSchema("set_spectator_policy", SetSpectatorPolicy),
   __reducerSchema("start_game", StartGame),
+  __reducerSchema("start_new_season", StartNewSeason),
+  __reducerSchema("start_tournament", StartTournament),
+  __reducerSchema("transfer_host", TransferHost),
+  __reducerSchema("unlink_farcaster", UnlinkFarcaster),
+  __reducerSchema("unmute_user", UnmuteUser),
+  __reducerSchema("withdraw_from_tournament", WithdrawFromTournament),
 );
 
 /** The schema information for all procedures in this module. This is defined the same way as the procedures would have been defined in the server. */
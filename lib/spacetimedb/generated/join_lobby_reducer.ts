@@ -12,6 +12,5 @@ import {
 
 export default {
   lobbyId: __t.string(),
-  playerAddress: __t.string(),
-  playerName: __t.string(),
+  passcode: __t.option(__t.string()),
 };
@@ -20,6 +20,10 @@ export default __t.object("LobbyPlayer", {
   isHost: __t.bool(),
   isAlive: __t.bool(),
   hasDeposited: __t.bool(),
+  isReady: __t.bool(),
+  timeBankMs: __t.u64(),
+  eliminatedAtMove: __t.option(__t.u32()),
+  fid: __t.option(__t.u64()),
   joinedAt: __t.timestamp(),
 });
 
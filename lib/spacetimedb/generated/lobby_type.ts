@@ -9,17 +9,37 @@ import {
   type AlgebraicTypeType as __AlgebraicTypeType,
   type Infer as __Infer,
 } from "spacetimedb";
+import LobbyStatus from "./lobby_status_type";
+import LobbyVisibility from "./lobby_visibility_type";
+import TimeControl from "./time_control_type";
 
 export default __t.object("Lobby", {
   id: __t.string(),
+  roomCode: __t.string(),
   chainId: __t.u32(),
   matchId: __t.u64(),
   arenaAddress: __t.string(),
   hostIdentity: __t.identity(),
   hostAddress: __t.string(),
-  entryFee: __t.string(),
+  entryFee: __t.u256(),
   maxPlayers: __t.u32(),
-  status: __t.string(),
+  get status() {
+    return LobbyStatus;
+  },
+  get timeControl() {
+    return TimeControl;
+  },
+  isLocked: __t.bool(),
+  get visibility() {
+    return LobbyVisibility;
+  },
+  hasPasscode: __t.bool(),
+  allowSpectators: __t.bool(),
+  maxSpectators: __t.option(__t.u32()),
+  spectatorCount: __t.u32(),
+  previousLobbyId: __t.option(__t.string()),
+  rematchLobbyId: __t.option(__t.string()),
+  tournamentId: __t.option(__t.string()),
   winnerIdentity: __t.option(__t.identity()),
   winnerAddress: __t.option(__t.string()),
   createdAt: __t.timestamp(),
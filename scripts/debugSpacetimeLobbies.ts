@@ -34,7 +34,7 @@ async function main() {
                             if (lobby) {
                                 console.log(`\nMatch #${matchId}: FOUND`);
                                 console.log(`  ID: ${lobby.id}`);
-                                console.log(`  Status: ${lobby.status}`);
+                                console.log(`  Status: ${lobby.status.tag}`);
                                 console.log(`  Winner: ${lobby.winnerAddress}`);
                                 console.log(`  Created: ${lobby.createdAt}`);
                             } else {
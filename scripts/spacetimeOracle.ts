@@ -34,6 +34,8 @@ const ORACLE_PK = oraclePk as `0x${string}`;
 const SPACETIMEDB_CONFIG = {
   host: process.env.NEXT_PUBLIC_SPACETIMEDB_HOST || "wss://maincloud.spacetimedb.com",
   moduleName: process.env.NEXT_PUBLIC_SPACETIMEDB_MODULE || "chain-reaction",
+  // Token for an identity registered with register_oracle; oracle-only reducers reject anyone else
+  token: process.env.STDB_ORACLE_TOKEN,
 };
 
 // RPC URLs (optional, uses default if not provided)
@@ -103,7 +105,7 @@ async function finalizeMatchOnChain(
   lobby: LobbyType
 ): Promise<{ success: boolean; txHash?: string; error?: string }> {
   try {
-    if (lobby.status !== "finished") {
+    if (lobby.status.tag !== "Finished") {
       return { success: false, error: "Lobby is not finished" };
     }
 
@@ -185,23 +187,16 @@ async function finalizeMatchOnChain(
 }
 
 /**
- * Mark lobby as settled in SpacetimeDB
- * NOTE: This requires a reducer in the SpacetimeDB module to update the lobby
- * For now, we'll just log - you may need to add a reducer or update field
+ * Tell SpacetimeDB the match is finished on-chain. Lobbies with payouts owed
+ * wait in Finished until this moves them on to Settling.
  */
-async function markLobbyAsSettled(
+async function markMatchFinished(
   connection: DbConnection,
   lobbyId: string,
   txHash: string
 ): Promise<void> {
-  // NOTE: The current SpacetimeDB schema doesn't have a settled_onchain field
-  // You'll need to either:
-  // 1. Add a reducer to update the lobby status to "settled"
-  // 2. Add a settled_onchain field to the Lobby table in Rust
-  // 3. Use the existing status field and change "finished" to "settled"
-
-  console.log(`Would mark lobby ${lobbyId} as settled with tx ${txHash}`);
-  // TODO: Call a reducer like: connection.reducers.markSettled({ lobbyId, txHash });
+  console.log(`Marking lobby ${lobbyId} finished on-chain with tx ${txHash || "(unknown)"}`);
+  connection.reducers.recordMatchFinished({ lobbyId, txHash });
 }
 
 /**
@@ -223,6 +218,7 @@ async function main() {
       const builder = DbConnection.builder()
         .withUri(SPACETIMEDB_CONFIG.host)
         .withModuleName(SPACETIMEDB_CONFIG.moduleName)
+        .withToken(SPACETIMEDB_CONFIG.token)
         .onConnect((conn) => {
           clearTimeout(timeout);
           console.log("✅ Connected to SpacetimeDB");
@@ -260,7 +256,7 @@ async function main() {
           try {
             const allLobbies = Array.from(ctx.db.lobby.iter());
             const finishedLobbies = allLobbies.filter(
-              (lobby: LobbyRowType) => lobby.status === "finished" && lobby.winnerAddress
+              (lobby: LobbyRowType) => lobby.status.tag === "Finished" && lobby.winnerAddress
             );
 
             console.log(`Found ${finishedLobbies.length} finished lobby/lobbies`);
@@ -284,7 +280,7 @@ async function main() {
     // Listen for updates to lobby table
     connection.db.lobby.onUpdate((ctx, oldRow, newRow) => {
       // Only process when status changes from non-finished to finished
-      if (oldRow.status !== "finished" && newRow.status === "finished" && newRow.winnerAddress) {
+      if (oldRow.status.tag !== "Finished" && newRow.status.tag === "Finished" && newRow.winnerAddress) {
         console.log(`🎯 New finished lobby detected: ${newRow.id}`);
         processFinishedLobby(newRow as unknown as LobbyType, connection!);
       }
@@ -325,7 +321,9 @@ async function processFinishedLobby(lobby: LobbyType, connection: DbConnection):
   const alreadyFinalized = await isMatchFinalized(lobby.chainId, BigInt(lobby.matchId));
 
   if (alreadyFinalized) {
+    // Finalized by an earlier run whose reducer call didn't land
     console.log(`   ⏭️  Match ${lobby.matchId} already finalized on-chain, skipping`);
+    await markMatchFinished(connection, lobby.id, "");
     return;
   }
 
@@ -336,10 +334,11 @@ async function processFinishedLobby(lobby: LobbyType, connection: DbConnection):
       console.log(`   ✅ Successfully finalized match ${lobby.matchId} on-chain`);
       console.log(`   📝 Transaction: ${result.txHash}`);
 
-      // Mark as settled (requires reducer or manual update)
-      if (result.txHash !== "already-finalized") {
-        await markLobbyAsSettled(connection, lobby.id, result.txHash);
-      }
+      await markMatchFinished(
+        connection,
+        lobby.id,
+        result.txHash === "already-finalized" ? "" : result.txHash
+      );
     } else {
       console.error(`   ❌ Failed to finalize match: ${result.error}`);
       // You might want to retry logic here or alert
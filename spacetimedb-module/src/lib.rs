@@ -1,6 +1,553 @@
-use spacetimedb::{ReducerContext, Table, Identity, Timestamp};
+use spacetimedb::{ReducerContext, Table, Identity, Timestamp, SpacetimeType, ScheduleAt};
+use spacetimedb::rand::Rng;
+use spacetimedb::sats::u256;
+use sha3::{Digest, Keccak256, Sha3_256};
 use serde::{Deserialize, Serialize};
 
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+/// ErrorCode - Why a reducer call was rejected. Reducers return these rather
+/// than panicking, so a panic always means a broken invariant. Clients get
+/// "CODE: message" back and should branch on the code, which never changes;
+/// the message is for people and may be reworded. log_rejections logs each
+/// one with its caller. Every code is listed in the public error_code table.
+#[derive(SpacetimeType, Debug, Clone, Copy, PartialEq)]
+pub enum ErrorCode {
+    // Lookups
+    LobbyNotFound,
+    TournamentNotFound,
+    SettlementNotFound,
+    RefundNotFound,
+    MessageNotFound,
+    InviteNotFound,
+    RematchNotFound,
+    ProfileNotFound,
+    NonceNotFound,
+
+    // Permissions
+    HostOnly,
+    ModeratorOnly,
+    OracleOnly,
+    OrganizerOnly,
+    SchedulerOnly,
+
+    // Lobby membership
+    NotInLobby,
+    PlayerNotInLobby,
+    NotAnActivePlayer,
+    AlreadyInLobby,
+    LobbyLocked,
+    LobbyFull,
+    LobbyPrivate,
+    SeatsReserved,
+    AlreadyInvited,
+    RematchReserved,
+    NoFreeSeats,
+    LobbyNotAcceptingPlayers,
+    LobbyNotActive,
+    TournamentSeat,
+    CannotKickSelf,
+    AlreadyHost,
+    PlayerEliminated,
+
+    // Game flow
+    GameAlreadyStarted,
+    GameNotLive,
+    LobbyNotFinished,
+    NotEnoughPlayers,
+    NotAllDeposited,
+    NotAllReady,
+    NotYourTurn,
+    TurnLocked,
+    MoveTooFast,
+    InvalidPosition,
+    CellOwned,
+    TurnNotTimedOut,
+    MatchIdAlreadySet,
+    RematchAlreadyRequested,
+    RematchClosed,
+    IllegalLobbyTransition,
+    NoActivePlayers,
+
+    // Colours
+    UnknownColour,
+    ColourTaken,
+    NoColoursAvailable,
+
+    // Lobby settings
+    InvalidCorrespondenceHours,
+    InvalidClockBank,
+    InvalidClockIncrement,
+    InvalidPlayerCount,
+    RoomCodesExhausted,
+    InvalidPasscode,
+    PasscodeRequiresPrivate,
+    InvalidInviteDuration,
+    InviteTargetMissing,
+    InvalidAmount,
+    EntryFeeTooHigh,
+    PrizePoolOverflow,
+
+    // Spectators
+    SpectatorsNotAllowed,
+    SpectatorLimitReached,
+    PlayerCannotSpectate,
+    AlreadySpectating,
+    NotSpectating,
+
+    // Chat and moderation
+    Muted,
+    ChatRateLimited,
+    MessageEmpty,
+    MessageTooLong,
+    NotInChat,
+    CannotMuteModerator,
+    NotMuted,
+    AlreadyModerator,
+    NotAModerator,
+    CannotRemoveSelf,
+
+    // Wallets and profiles
+    InvalidAddress,
+    InvalidSignature,
+    SignatureMismatch,
+    NonceExpired,
+    NonceMissing,
+    MessageMismatch,
+    InvalidSiweMessage,
+    SiweDomainMismatch,
+    SiweChainNotAllowed,
+    WalletNotLinked,
+    InvalidDisplayName,
+    UrlTooLong,
+    InvalidFarcasterUsername,
+    FarcasterNotLinked,
+    FarcasterAccountUnknown,
+    NotCustodyAddress,
+
+    // Matchmaking
+    AlreadyQueued,
+    NotQueued,
+
+    // Settlements and oracles
+    SettlementAlreadyExecuted,
+    RefundAlreadyProcessed,
+    MatchNotAwaitingOracle,
+    AlreadyOracle,
+    NotAnOracle,
+
+    // Seasons
+    InvalidSeasonName,
+
+    // Tournaments
+    InvalidTournamentName,
+    InvalidEntryLimit,
+    InvalidRoundDeadline,
+    SwissHeadToHead,
+    InvalidSwissRounds,
+    RegistrationClosed,
+    TournamentFull,
+    AlreadyRegistered,
+    NotRegistered,
+    TournamentAlreadyStarted,
+    NotEnoughEntries,
+    TournamentNotRunning,
+    RoundNotTimedOut,
+    RoundInProgress,
+}
+
+impl ErrorCode {
+    /// Every variant, in declaration order
+    pub const ALL: [ErrorCode; 116] = [
+        ErrorCode::LobbyNotFound,
+        ErrorCode::TournamentNotFound,
+        ErrorCode::SettlementNotFound,
+        ErrorCode::RefundNotFound,
+        ErrorCode::MessageNotFound,
+        ErrorCode::InviteNotFound,
+        ErrorCode::RematchNotFound,
+        ErrorCode::ProfileNotFound,
+        ErrorCode::NonceNotFound,
+        ErrorCode::HostOnly,
+        ErrorCode::ModeratorOnly,
+        ErrorCode::OracleOnly,
+        ErrorCode::OrganizerOnly,
+        ErrorCode::SchedulerOnly,
+        ErrorCode::NotInLobby,
+        ErrorCode::PlayerNotInLobby,
+        ErrorCode::NotAnActivePlayer,
+        ErrorCode::AlreadyInLobby,
+        ErrorCode::LobbyLocked,
+        ErrorCode::LobbyFull,
+        ErrorCode::LobbyPrivate,
+        ErrorCode::SeatsReserved,
+        ErrorCode::AlreadyInvited,
+        ErrorCode::RematchReserved,
+        ErrorCode::NoFreeSeats,
+        ErrorCode::LobbyNotAcceptingPlayers,
+        ErrorCode::LobbyNotActive,
+        ErrorCode::TournamentSeat,
+        ErrorCode::CannotKickSelf,
+        ErrorCode::AlreadyHost,
+        ErrorCode::PlayerEliminated,
+        ErrorCode::GameAlreadyStarted,
+        ErrorCode::GameNotLive,
+        ErrorCode::LobbyNotFinished,
+        ErrorCode::NotEnoughPlayers,
+        ErrorCode::NotAllDeposited,
+        ErrorCode::NotAllReady,
+        ErrorCode::NotYourTurn,
+        ErrorCode::TurnLocked,
+        ErrorCode::MoveTooFast,
+        ErrorCode::InvalidPosition,
+        ErrorCode::CellOwned,
+        ErrorCode::TurnNotTimedOut,
+        ErrorCode::MatchIdAlreadySet,
+        ErrorCode::RematchAlreadyRequested,
+        ErrorCode::RematchClosed,
+        ErrorCode::IllegalLobbyTransition,
+        ErrorCode::NoActivePlayers,
+        ErrorCode::UnknownColour,
+        ErrorCode::ColourTaken,
+        ErrorCode::NoColoursAvailable,
+        ErrorCode::InvalidCorrespondenceHours,
+        ErrorCode::InvalidClockBank,
+        ErrorCode::InvalidClockIncrement,
+        ErrorCode::InvalidPlayerCount,
+        ErrorCode::RoomCodesExhausted,
+        ErrorCode::InvalidPasscode,
+        ErrorCode::PasscodeRequiresPrivate,
+        ErrorCode::InvalidInviteDuration,
+        ErrorCode::InviteTargetMissing,
+        ErrorCode::InvalidAmount,
+        ErrorCode::EntryFeeTooHigh,
+        ErrorCode::PrizePoolOverflow,
+        ErrorCode::SpectatorsNotAllowed,
+        ErrorCode::SpectatorLimitReached,
+        ErrorCode::PlayerCannotSpectate,
+        ErrorCode::AlreadySpectating,
+        ErrorCode::NotSpectating,
+        ErrorCode::Muted,
+        ErrorCode::ChatRateLimited,
+        ErrorCode::MessageEmpty,
+        ErrorCode::MessageTooLong,
+        ErrorCode::NotInChat,
+        ErrorCode::CannotMuteModerator,
+        ErrorCode::NotMuted,
+        ErrorCode::AlreadyModerator,
+        ErrorCode::NotAModerator,
+        ErrorCode::CannotRemoveSelf,
+        ErrorCode::InvalidAddress,
+        ErrorCode::InvalidSignature,
+        ErrorCode::SignatureMismatch,
+        ErrorCode::NonceExpired,
+        ErrorCode::NonceMissing,
+        ErrorCode::MessageMismatch,
+        ErrorCode::InvalidSiweMessage,
+        ErrorCode::SiweDomainMismatch,
+        ErrorCode::SiweChainNotAllowed,
+        ErrorCode::WalletNotLinked,
+        ErrorCode::InvalidDisplayName,
+        ErrorCode::UrlTooLong,
+        ErrorCode::InvalidFarcasterUsername,
+        ErrorCode::FarcasterNotLinked,
+        ErrorCode::FarcasterAccountUnknown,
+        ErrorCode::NotCustodyAddress,
+        ErrorCode::AlreadyQueued,
+        ErrorCode::NotQueued,
+        ErrorCode::SettlementAlreadyExecuted,
+        ErrorCode::RefundAlreadyProcessed,
+        ErrorCode::MatchNotAwaitingOracle,
+        ErrorCode::AlreadyOracle,
+        ErrorCode::NotAnOracle,
+        ErrorCode::InvalidSeasonName,
+        ErrorCode::InvalidTournamentName,
+        ErrorCode::InvalidEntryLimit,
+        ErrorCode::InvalidRoundDeadline,
+        ErrorCode::SwissHeadToHead,
+        ErrorCode::InvalidSwissRounds,
+        ErrorCode::RegistrationClosed,
+        ErrorCode::TournamentFull,
+        ErrorCode::AlreadyRegistered,
+        ErrorCode::NotRegistered,
+        ErrorCode::TournamentAlreadyStarted,
+        ErrorCode::NotEnoughEntries,
+        ErrorCode::TournamentNotRunning,
+        ErrorCode::RoundNotTimedOut,
+        ErrorCode::RoundInProgress,
+    ];
+
+    /// The message part of "CODE: message"
+    pub fn message(self) -> String {
+        let text = self.to_string();
+        text[self.code().len() + 2..].to_string()
+    }
+
+    /// Stable identifier clients branch on
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorCode::LobbyNotFound => "LOBBY_NOT_FOUND",
+            ErrorCode::TournamentNotFound => "TOURNAMENT_NOT_FOUND",
+            ErrorCode::SettlementNotFound => "SETTLEMENT_NOT_FOUND",
+            ErrorCode::RefundNotFound => "REFUND_NOT_FOUND",
+            ErrorCode::MessageNotFound => "MESSAGE_NOT_FOUND",
+            ErrorCode::InviteNotFound => "INVITE_NOT_FOUND",
+            ErrorCode::RematchNotFound => "REMATCH_NOT_FOUND",
+            ErrorCode::ProfileNotFound => "PROFILE_NOT_FOUND",
+            ErrorCode::NonceNotFound => "NONCE_NOT_FOUND",
+            ErrorCode::HostOnly => "HOST_ONLY",
+            ErrorCode::ModeratorOnly => "MODERATOR_ONLY",
+            ErrorCode::OracleOnly => "ORACLE_ONLY",
+            ErrorCode::OrganizerOnly => "ORGANIZER_ONLY",
+            ErrorCode::SchedulerOnly => "SCHEDULER_ONLY",
+            ErrorCode::NotInLobby => "NOT_IN_LOBBY",
+            ErrorCode::PlayerNotInLobby => "PLAYER_NOT_IN_LOBBY",
+            ErrorCode::NotAnActivePlayer => "NOT_AN_ACTIVE_PLAYER",
+            ErrorCode::AlreadyInLobby => "ALREADY_IN_LOBBY",
+            ErrorCode::LobbyLocked => "LOBBY_LOCKED",
+            ErrorCode::LobbyFull => "LOBBY_FULL",
+            ErrorCode::LobbyPrivate => "LOBBY_PRIVATE",
+            ErrorCode::SeatsReserved => "SEATS_RESERVED",
+            ErrorCode::AlreadyInvited => "ALREADY_INVITED",
+            ErrorCode::RematchReserved => "REMATCH_RESERVED",
+            ErrorCode::NoFreeSeats => "NO_FREE_SEATS",
+            ErrorCode::LobbyNotAcceptingPlayers => "LOBBY_NOT_ACCEPTING_PLAYERS",
+            ErrorCode::LobbyNotActive => "LOBBY_NOT_ACTIVE",
+            ErrorCode::TournamentSeat => "TOURNAMENT_SEAT",
+            ErrorCode::CannotKickSelf => "CANNOT_KICK_SELF",
+            ErrorCode::AlreadyHost => "ALREADY_HOST",
+            ErrorCode::PlayerEliminated => "PLAYER_ELIMINATED",
+            ErrorCode::GameAlreadyStarted => "GAME_ALREADY_STARTED",
+            ErrorCode::GameNotLive => "GAME_NOT_LIVE",
+            ErrorCode::LobbyNotFinished => "LOBBY_NOT_FINISHED",
+            ErrorCode::NotEnoughPlayers => "NOT_ENOUGH_PLAYERS",
+            ErrorCode::NotAllDeposited => "NOT_ALL_DEPOSITED",
+            ErrorCode::NotAllReady => "NOT_ALL_READY",
+            ErrorCode::NotYourTurn => "NOT_YOUR_TURN",
+            ErrorCode::TurnLocked => "TURN_LOCKED",
+            ErrorCode::MoveTooFast => "MOVE_TOO_FAST",
+            ErrorCode::InvalidPosition => "INVALID_POSITION",
+            ErrorCode::CellOwned => "CELL_OWNED",
+            ErrorCode::TurnNotTimedOut => "TURN_NOT_TIMED_OUT",
+            ErrorCode::MatchIdAlreadySet => "MATCH_ID_ALREADY_SET",
+            ErrorCode::RematchAlreadyRequested => "REMATCH_ALREADY_REQUESTED",
+            ErrorCode::RematchClosed => "REMATCH_CLOSED",
+            ErrorCode::IllegalLobbyTransition => "ILLEGAL_LOBBY_TRANSITION",
+            ErrorCode::NoActivePlayers => "NO_ACTIVE_PLAYERS",
+            ErrorCode::UnknownColour => "UNKNOWN_COLOUR",
+            ErrorCode::ColourTaken => "COLOUR_TAKEN",
+            ErrorCode::NoColoursAvailable => "NO_COLOURS_AVAILABLE",
+            ErrorCode::InvalidCorrespondenceHours => "INVALID_CORRESPONDENCE_HOURS",
+            ErrorCode::InvalidClockBank => "INVALID_CLOCK_BANK",
+            ErrorCode::InvalidClockIncrement => "INVALID_CLOCK_INCREMENT",
+            ErrorCode::InvalidPlayerCount => "INVALID_PLAYER_COUNT",
+            ErrorCode::RoomCodesExhausted => "ROOM_CODES_EXHAUSTED",
+            ErrorCode::InvalidPasscode => "INVALID_PASSCODE",
+            ErrorCode::PasscodeRequiresPrivate => "PASSCODE_REQUIRES_PRIVATE",
+            ErrorCode::InvalidInviteDuration => "INVALID_INVITE_DURATION",
+            ErrorCode::InviteTargetMissing => "INVITE_TARGET_MISSING",
+            ErrorCode::InvalidAmount => "INVALID_AMOUNT",
+            ErrorCode::EntryFeeTooHigh => "ENTRY_FEE_TOO_HIGH",
+            ErrorCode::PrizePoolOverflow => "PRIZE_POOL_OVERFLOW",
+            ErrorCode::SpectatorsNotAllowed => "SPECTATORS_NOT_ALLOWED",
+            ErrorCode::SpectatorLimitReached => "SPECTATOR_LIMIT_REACHED",
+            ErrorCode::PlayerCannotSpectate => "PLAYER_CANNOT_SPECTATE",
+            ErrorCode::AlreadySpectating => "ALREADY_SPECTATING",
+            ErrorCode::NotSpectating => "NOT_SPECTATING",
+            ErrorCode::Muted => "MUTED",
+            ErrorCode::ChatRateLimited => "CHAT_RATE_LIMITED",
+            ErrorCode::MessageEmpty => "MESSAGE_EMPTY",
+            ErrorCode::MessageTooLong => "MESSAGE_TOO_LONG",
+            ErrorCode::NotInChat => "NOT_IN_CHAT",
+            ErrorCode::CannotMuteModerator => "CANNOT_MUTE_MODERATOR",
+            ErrorCode::NotMuted => "NOT_MUTED",
+            ErrorCode::AlreadyModerator => "ALREADY_MODERATOR",
+            ErrorCode::NotAModerator => "NOT_A_MODERATOR",
+            ErrorCode::CannotRemoveSelf => "CANNOT_REMOVE_SELF",
+            ErrorCode::InvalidAddress => "INVALID_ADDRESS",
+            ErrorCode::InvalidSignature => "INVALID_SIGNATURE",
+            ErrorCode::SignatureMismatch => "SIGNATURE_MISMATCH",
+            ErrorCode::NonceExpired => "NONCE_EXPIRED",
+            ErrorCode::NonceMissing => "NONCE_MISSING",
+            ErrorCode::MessageMismatch => "MESSAGE_MISMATCH",
+            ErrorCode::InvalidSiweMessage => "INVALID_SIWE_MESSAGE",
+            ErrorCode::SiweDomainMismatch => "SIWE_DOMAIN_MISMATCH",
+            ErrorCode::SiweChainNotAllowed => "SIWE_CHAIN_NOT_ALLOWED",
+            ErrorCode::WalletNotLinked => "WALLET_NOT_LINKED",
+            ErrorCode::InvalidDisplayName => "INVALID_DISPLAY_NAME",
+            ErrorCode::UrlTooLong => "URL_TOO_LONG",
+            ErrorCode::InvalidFarcasterUsername => "INVALID_FARCASTER_USERNAME",
+            ErrorCode::FarcasterNotLinked => "FARCASTER_NOT_LINKED",
+            ErrorCode::FarcasterAccountUnknown => "FARCASTER_ACCOUNT_UNKNOWN",
+            ErrorCode::NotCustodyAddress => "NOT_CUSTODY_ADDRESS",
+            ErrorCode::AlreadyQueued => "ALREADY_QUEUED",
+            ErrorCode::NotQueued => "NOT_QUEUED",
+            ErrorCode::SettlementAlreadyExecuted => "SETTLEMENT_ALREADY_EXECUTED",
+            ErrorCode::RefundAlreadyProcessed => "REFUND_ALREADY_PROCESSED",
+            ErrorCode::MatchNotAwaitingOracle => "MATCH_NOT_AWAITING_ORACLE",
+            ErrorCode::AlreadyOracle => "ALREADY_ORACLE",
+            ErrorCode::NotAnOracle => "NOT_AN_ORACLE",
+            ErrorCode::InvalidSeasonName => "INVALID_SEASON_NAME",
+            ErrorCode::InvalidTournamentName => "INVALID_TOURNAMENT_NAME",
+            ErrorCode::InvalidEntryLimit => "INVALID_ENTRY_LIMIT",
+            ErrorCode::InvalidRoundDeadline => "INVALID_ROUND_DEADLINE",
+            ErrorCode::SwissHeadToHead => "SWISS_HEAD_TO_HEAD",
+            ErrorCode::InvalidSwissRounds => "INVALID_SWISS_ROUNDS",
+            ErrorCode::RegistrationClosed => "REGISTRATION_CLOSED",
+            ErrorCode::TournamentFull => "TOURNAMENT_FULL",
+            ErrorCode::AlreadyRegistered => "ALREADY_REGISTERED",
+            ErrorCode::NotRegistered => "NOT_REGISTERED",
+            ErrorCode::TournamentAlreadyStarted => "TOURNAMENT_ALREADY_STARTED",
+            ErrorCode::NotEnoughEntries => "NOT_ENOUGH_ENTRIES",
+            ErrorCode::TournamentNotRunning => "TOURNAMENT_NOT_RUNNING",
+            ErrorCode::RoundNotTimedOut => "ROUND_NOT_TIMED_OUT",
+            ErrorCode::RoundInProgress => "ROUND_IN_PROGRESS",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = self.code();
+        match self {
+            ErrorCode::LobbyNotFound => write!(f, "{}: Lobby not found", code),
+            ErrorCode::TournamentNotFound => write!(f, "{}: Tournament not found", code),
+            ErrorCode::SettlementNotFound => write!(f, "{}: Settlement not found", code),
+            ErrorCode::RefundNotFound => write!(f, "{}: Refund not found", code),
+            ErrorCode::MessageNotFound => write!(f, "{}: Message not found", code),
+            ErrorCode::InviteNotFound => write!(f, "{}: Invite not found", code),
+            ErrorCode::RematchNotFound => write!(f, "{}: No rematch requested for this lobby", code),
+            ErrorCode::ProfileNotFound => write!(f, "{}: Set up your profile first", code),
+            ErrorCode::NonceNotFound => write!(f, "{}: Request a nonce first", code),
+            ErrorCode::HostOnly => write!(f, "{}: Only the host can do that", code),
+            ErrorCode::ModeratorOnly => write!(f, "{}: Only moderators can do that", code),
+            ErrorCode::OracleOnly => write!(f, "{}: Only the oracle can do that", code),
+            ErrorCode::OrganizerOnly => write!(f, "{}: Only the organizer can do that", code),
+            ErrorCode::SchedulerOnly => write!(f, "{}: Only the scheduler can run this reducer", code),
+            ErrorCode::NotInLobby => write!(f, "{}: Not in this lobby", code),
+            ErrorCode::PlayerNotInLobby => write!(f, "{}: Player is not in this lobby", code),
+            ErrorCode::NotAnActivePlayer => write!(f, "{}: Not an active player in this game", code),
+            ErrorCode::AlreadyInLobby => write!(f, "{}: Already joined this lobby", code),
+            ErrorCode::LobbyLocked => write!(f, "{}: Lobby is locked", code),
+            ErrorCode::LobbyFull => write!(f, "{}: Lobby is full", code),
+            ErrorCode::LobbyPrivate => write!(f, "{}: This lobby is private", code),
+            ErrorCode::SeatsReserved => write!(f, "{}: Remaining seats are reserved for invited players", code),
+            ErrorCode::AlreadyInvited => write!(f, "{}: Player already has an open invite to this lobby", code),
+            ErrorCode::RematchReserved => write!(f, "{}: Rematch seats are reserved for the original players", code),
+            ErrorCode::NoFreeSeats => write!(f, "{}: No free seats to reserve", code),
+            ErrorCode::LobbyNotAcceptingPlayers => write!(f, "{}: Lobby is not accepting players", code),
+            ErrorCode::LobbyNotActive => write!(f, "{}: Lobby is no longer active", code),
+            ErrorCode::TournamentSeat => write!(f, "{}: Tournament match seats cannot change; unplayed matches are forfeited at the round deadline", code),
+            ErrorCode::CannotKickSelf => write!(f, "{}: Host cannot kick themselves", code),
+            ErrorCode::AlreadyHost => write!(f, "{}: Already the host", code),
+            ErrorCode::PlayerEliminated => write!(f, "{}: Cannot transfer host to an eliminated player", code),
+            ErrorCode::GameAlreadyStarted => write!(f, "{}: Game already started", code),
+            ErrorCode::GameNotLive => write!(f, "{}: Game is not live", code),
+            ErrorCode::LobbyNotFinished => write!(f, "{}: Can only rematch a finished lobby", code),
+            ErrorCode::NotEnoughPlayers => write!(f, "{}: Need at least 2 players to start", code),
+            ErrorCode::NotAllDeposited => write!(f, "{}: Not all players have deposited USDC", code),
+            ErrorCode::NotAllReady => write!(f, "{}: Not all players are ready", code),
+            ErrorCode::NotYourTurn => write!(f, "{}: Not your turn", code),
+            ErrorCode::TurnLocked => write!(f, "{}: Turn is locked, please retry in 50ms", code),
+            ErrorCode::MoveTooFast => write!(f, "{}: Move too fast, wait 300ms between moves", code),
+            ErrorCode::InvalidPosition => write!(f, "{}: Invalid position", code),
+            ErrorCode::CellOwned => write!(f, "{}: Cell owned by another player", code),
+            ErrorCode::TurnNotTimedOut => write!(f, "{}: Turn has not timed out yet", code),
+            ErrorCode::MatchIdAlreadySet => write!(f, "{}: Match id already set", code),
+            ErrorCode::RematchAlreadyRequested => write!(f, "{}: Rematch already requested", code),
+            ErrorCode::RematchClosed => write!(f, "{}: Rematch is no longer accepting players", code),
+            ErrorCode::IllegalLobbyTransition => write!(f, "{}: Lobby can't move to that status from its current one", code),
+            ErrorCode::NoActivePlayers => write!(f, "{}: Game has no active players", code),
+            ErrorCode::UnknownColour => write!(f, "{}: Unknown colour", code),
+            ErrorCode::ColourTaken => write!(f, "{}: Colour already taken", code),
+            ErrorCode::NoColoursAvailable => write!(f, "{}: No colours available", code),
+            ErrorCode::InvalidCorrespondenceHours => write!(f, "{}: Correspondence turns must be between 1 and {} hours", code, MAX_CORRESPONDENCE_HOURS),
+            ErrorCode::InvalidClockBank => write!(f, "{}: Clock time bank must be between 1 and {} seconds", code, MAX_CLOCK_BANK_SECS),
+            ErrorCode::InvalidClockIncrement => write!(f, "{}: Clock increment cannot exceed {} seconds", code, MAX_CLOCK_INCREMENT_SECS),
+            ErrorCode::InvalidPlayerCount => write!(f, "{}: Matches are for 2 to 5 players", code),
+            ErrorCode::RoomCodesExhausted => write!(f, "{}: No unused room code could be found, please try again", code),
+            ErrorCode::InvalidPasscode => write!(f, "{}: Passcode must be between {} and {} characters", code, MIN_PASSCODE_LENGTH, MAX_PASSCODE_LENGTH),
+            ErrorCode::PasscodeRequiresPrivate => write!(f, "{}: Only private lobbies can have a passcode", code),
+            ErrorCode::InvalidInviteDuration => write!(f, "{}: Invite must last between 1 and {} minutes", code, MAX_INVITE_TTL_MINUTES),
+            ErrorCode::InviteTargetMissing => write!(f, "{}: Invite needs an address or identity", code),
+            ErrorCode::InvalidAmount => write!(f, "{}: Amount must be a whole number of token base units that fits in 256 bits", code),
+            ErrorCode::EntryFeeTooHigh => write!(f, "{}: Entry fee cannot exceed {} token base units", code, MAX_ENTRY_FEE),
+            ErrorCode::PrizePoolOverflow => write!(f, "{}: Prize pool is too large to settle", code),
+            ErrorCode::SpectatorsNotAllowed => write!(f, "{}: Spectators are not allowed in this lobby", code),
+            ErrorCode::SpectatorLimitReached => write!(f, "{}: Spectator limit reached", code),
+            ErrorCode::PlayerCannotSpectate => write!(f, "{}: Players cannot spectate their own lobby", code),
+            ErrorCode::AlreadySpectating => write!(f, "{}: Already spectating this lobby", code),
+            ErrorCode::NotSpectating => write!(f, "{}: Not spectating this lobby", code),
+            ErrorCode::Muted => write!(f, "{}: You are muted", code),
+            ErrorCode::ChatRateLimited => write!(f, "{}: Sending messages too fast, slow down", code),
+            ErrorCode::MessageEmpty => write!(f, "{}: Message is empty", code),
+            ErrorCode::MessageTooLong => write!(f, "{}: Message too long, max {} characters", code, MAX_CHAT_LENGTH),
+            ErrorCode::NotInChat => write!(f, "{}: Only players and spectators can chat in this lobby", code),
+            ErrorCode::CannotMuteModerator => write!(f, "{}: Cannot mute a moderator", code),
+            ErrorCode::NotMuted => write!(f, "{}: User is not muted", code),
+            ErrorCode::AlreadyModerator => write!(f, "{}: Already a moderator", code),
+            ErrorCode::NotAModerator => write!(f, "{}: Not a moderator", code),
+            ErrorCode::CannotRemoveSelf => write!(f, "{}: Cannot remove yourself as moderator", code),
+            ErrorCode::InvalidAddress => write!(f, "{}: Invalid wallet address", code),
+            ErrorCode::InvalidSignature => write!(f, "{}: Invalid signature", code),
+            ErrorCode::SignatureMismatch => write!(f, "{}: Signature was not made by the expected wallet", code),
+            ErrorCode::NonceExpired => write!(f, "{}: Nonce expired, request a new one", code),
+            ErrorCode::NonceMissing => write!(f, "{}: Message does not contain the issued nonce", code),
+            ErrorCode::MessageMismatch => write!(f, "{}: Message does not name what is being linked", code),
+            ErrorCode::InvalidSiweMessage => write!(f, "{}: Message is not a valid Sign-In with Ethereum message", code),
+            ErrorCode::SiweDomainMismatch => write!(f, "{}: Message was not issued for {}", code, SIWE_DOMAIN),
+            ErrorCode::SiweChainNotAllowed => write!(f, "{}: Message names an unsupported chain", code),
+            ErrorCode::WalletNotLinked => write!(f, "{}: Wallet is not linked to this identity", code),
+            ErrorCode::InvalidDisplayName => write!(f, "{}: Display name must be between 1 and {} characters", code, MAX_DISPLAY_NAME_LENGTH),
+            ErrorCode::UrlTooLong => write!(f, "{}: URL too long", code),
+            ErrorCode::InvalidFarcasterUsername => write!(f, "{}: Invalid Farcaster username", code),
+            ErrorCode::FarcasterNotLinked => write!(f, "{}: No Farcaster account linked", code),
+            ErrorCode::FarcasterAccountUnknown => write!(f, "{}: Farcaster account has not been confirmed by the oracle yet", code),
+            ErrorCode::NotCustodyAddress => write!(f, "{}: Message was not signed by the custody address of the FID", code),
+            ErrorCode::AlreadyQueued => write!(f, "{}: Already in the queue", code),
+            ErrorCode::NotQueued => write!(f, "{}: Not in the queue", code),
+            ErrorCode::SettlementAlreadyExecuted => write!(f, "{}: Settlement already executed", code),
+            ErrorCode::RefundAlreadyProcessed => write!(f, "{}: Refund already processed", code),
+            ErrorCode::MatchNotAwaitingOracle => write!(f, "{}: Lobby is not waiting on the oracle to finish its match", code),
+            ErrorCode::AlreadyOracle => write!(f, "{}: Already an oracle", code),
+            ErrorCode::NotAnOracle => write!(f, "{}: Not an oracle", code),
+            ErrorCode::InvalidSeasonName => write!(f, "{}: Season needs a name", code),
+            ErrorCode::InvalidTournamentName => write!(f, "{}: Invalid tournament name", code),
+            ErrorCode::InvalidEntryLimit => write!(f, "{}: Tournaments take {} to {} entries", code, MIN_TOURNAMENT_ENTRIES, MAX_TOURNAMENT_ENTRIES),
+            ErrorCode::InvalidRoundDeadline => write!(f, "{}: Round deadline must be 1 to {} minutes", code, MAX_ROUND_DEADLINE_MINUTES),
+            ErrorCode::SwissHeadToHead => write!(f, "{}: Swiss tournaments are played head to head", code),
+            ErrorCode::InvalidSwissRounds => write!(f, "{}: Swiss tournaments play 1 to {} rounds", code, MAX_SWISS_ROUNDS),
+            ErrorCode::RegistrationClosed => write!(f, "{}: Registration is closed", code),
+            ErrorCode::TournamentFull => write!(f, "{}: Tournament is full", code),
+            ErrorCode::AlreadyRegistered => write!(f, "{}: Already registered", code),
+            ErrorCode::NotRegistered => write!(f, "{}: Not registered", code),
+            ErrorCode::TournamentAlreadyStarted => write!(f, "{}: Tournament already started", code),
+            ErrorCode::NotEnoughEntries => write!(f, "{}: Need at least {} entries", code, MIN_TOURNAMENT_ENTRIES),
+            ErrorCode::TournamentNotRunning => write!(f, "{}: Tournament is not running", code),
+            ErrorCode::RoundNotTimedOut => write!(f, "{}: Round has not timed out yet", code),
+            ErrorCode::RoundInProgress => write!(f, "{}: Remaining matches are still being played", code),
+        }
+    }
+}
+
+/// Publish every error code with its current message (safe to repeat)
+fn seed_error_codes(ctx: &ReducerContext) {
+    for kind in ErrorCode::ALL {
+        let row = ErrorCodeInfo {
+            code: kind.code().to_string(),
+            kind,
+            message: kind.message(),
+        };
+        if ctx.db.error_code().code().find(&row.code).is_some() {
+            ctx.db.error_code().code().update(row);
+        } else {
+            ctx.db.error_code().insert(row);
+        }
+    }
+}
+
 // ============================================================================
 // BOARD SIZE CONFIGURATION
 // ============================================================================
@@ -26,729 +573,5733 @@ fn get_board_size(max_players: u32) -> (usize, usize) {
 }
 
 // ============================================================================
-// TABLES
+// TIME CONTROL CONFIGURATION
 // ============================================================================
 
-/// Lobby - Represents a match room
-#[spacetimedb::table(name = lobby, public)]
-pub struct Lobby {
-    #[primary_key]
-    pub id: String,
-    pub chain_id: u32,           // 8453 (Base) or 42161 (Arbitrum)
-    pub match_id: u64,           // On-chain match ID
-    pub arena_address: String,   // ChainOrbArena contract address
-    pub host_identity: Identity,
-    pub host_address: String,    // Wallet address
-    pub entry_fee: String,       // In USDC wei units
-    pub max_players: u32,
-    pub status: String,          // "waiting" | "live" | "finished" | "cancelled"
-    pub winner_identity: Option<Identity>,
-    pub winner_address: Option<String>,
-    pub created_at: Timestamp,
-    pub updated_at: Timestamp,
+/// TimeControl - How long each player has to make a move
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub enum TimeControl {
+    Blitz,               // 10 seconds per turn
+    Standard,            // 30 seconds per turn
+    Correspondence(u32), // Hours per turn
+    Clock(ClockSettings), // Chess-clock time bank per player
 }
 
-/// LobbyPlayer - Players in a lobby
-#[spacetimedb::table(name = lobby_player, public)]
-#[derive(Clone)]
-pub struct LobbyPlayer {
-    #[primary_key]
-    pub id: String,              // lobbyId + "_" + identity hex
-    #[index(btree)]
-    pub lobby_id: String,
-    pub identity: Identity,
-    pub address: String,         // Wallet address
-    pub name: String,
-    pub color: String,           // "red" | "blue" | "green" | "yellow" | etc.
-    pub is_host: bool,
-    pub is_alive: bool,
-    pub has_deposited: bool,
-    pub joined_at: Timestamp,
+/// ClockSettings - Total time bank plus increment per move
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub struct ClockSettings {
+    pub bank_secs: u32,      // Starting time bank for each player
+    pub increment_secs: u32, // Added to the bank after every move
 }
 
-/// GameState - Current board state for a lobby
-#[spacetimedb::table(name = game_state, public)]
-pub struct GameState {
-    #[primary_key]
-    pub lobby_id: String,
-    pub board_json: String,       // JSON serialized board
-    pub rows: u32,                // Board height
-    pub cols: u32,                // Board width
-    pub current_player_index: u32,
-    pub move_count: u32,
-    pub last_move_at: Timestamp,
-    pub turn_deadline: Timestamp, // When the current turn expires
-    pub turn_lock_until: Option<Timestamp>, // Turn lock to prevent race conditions
-    pub last_move_player: Option<Identity>, // Last player who made a move
+// Correspondence games are capped at one week per turn
+const MAX_CORRESPONDENCE_HOURS: u32 = 168;
+
+// Chess-clock limits: up to 2 hours per player, up to 1 minute increment
+const MAX_CLOCK_BANK_SECS: u32 = 7200;
+const MAX_CLOCK_INCREMENT_SECS: u32 = 60;
+
+/// Get the time allowed for a single turn
+fn turn_duration(time_control: TimeControl) -> std::time::Duration {
+    match time_control {
+        TimeControl::Blitz => std::time::Duration::from_secs(10),
+        TimeControl::Standard => std::time::Duration::from_secs(30),
+        TimeControl::Correspondence(hours) => std::time::Duration::from_secs(hours as u64 * 3600),
+        TimeControl::Clock(clock) => std::time::Duration::from_secs(clock.bank_secs as u64),
+    }
 }
 
-/// GameMove - Individual moves for replay/verification
-#[spacetimedb::table(name = game_move, public)]
-pub struct GameMove {
-    #[primary_key]
-    pub id: String,               // lobbyId + "_" + moveIndex
-    #[index(btree)]
-    pub lobby_id: String,
-    pub move_index: u32,
-    pub player_identity: Identity,
-    pub row: u32,
-    pub col: u32,
-    pub timestamp: Timestamp,
+/// Validate a time control chosen at lobby creation
+fn validate_time_control(time_control: TimeControl) -> Result<(), ErrorCode> {
+    match time_control {
+        TimeControl::Correspondence(hours) if hours == 0 || hours > MAX_CORRESPONDENCE_HOURS => {
+            return Err(ErrorCode::InvalidCorrespondenceHours);
+        }
+        TimeControl::Clock(clock) if clock.bank_secs == 0 || clock.bank_secs > MAX_CLOCK_BANK_SECS => {
+            return Err(ErrorCode::InvalidClockBank);
+        }
+        TimeControl::Clock(clock) if clock.increment_secs > MAX_CLOCK_INCREMENT_SECS => {
+            return Err(ErrorCode::InvalidClockIncrement);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Get the starting time bank for a player (0 when not using a chess clock)
+fn initial_time_bank_ms(time_control: TimeControl) -> u64 {
+    match time_control {
+        TimeControl::Clock(clock) => clock.bank_secs as u64 * 1000,
+        _ => 0,
+    }
+}
+
+/// Get how long a player has for their turn (their remaining bank on a chess clock)
+fn turn_allowance(time_control: TimeControl, player: &LobbyPlayer) -> std::time::Duration {
+    match time_control {
+        TimeControl::Clock(_) => std::time::Duration::from_millis(player.time_bank_ms),
+        _ => turn_duration(time_control),
+    }
+}
+
+/// Charge a chess-clock move: the bank left after thinking plus the increment.
+/// None when the think time used up the bank, so the mover has flagged.
+fn charge_clock(time_bank_ms: u64, think_ms: u64, increment_secs: u32) -> Option<u64> {
+    time_bank_ms
+        .checked_sub(think_ms)
+        .map(|left| left + increment_secs as u64 * 1000)
 }
 
 // ============================================================================
-// BOARD TYPES
+// LOBBY STATE MACHINE
 // ============================================================================
 
-#[derive(Serialize, Deserialize, Clone, Default)]
-pub struct Cell {
-    pub orbs: u32,
-    pub owner: Option<String>,
+/// LobbyStatus - Where a lobby is in its lifecycle
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub enum LobbyStatus {
+    Waiting,   // Filling seats and collecting deposits
+    Live,      // Game in progress
+    Finished,  // Game over; waits here for the oracle when payouts are owed
+    Settling,  // Match finished on-chain, payouts being executed
+    Settled,   // Every payout executed (or nothing to pay)
+    Cancelled, // Closed before the game started
 }
 
-type Board = Vec<Vec<Cell>>;
+/// Whether a lobby may move from `from` to `to`
+fn is_legal_transition(from: LobbyStatus, to: LobbyStatus) -> bool {
+    use LobbyStatus::*;
+    matches!(
+        (from, to),
+        (Waiting, Live)
+            | (Waiting, Cancelled)
+            | (Live, Finished)
+            | (Finished, Settling)
+            | (Finished, Settled)
+            | (Settling, Settled)
+    )
+}
 
-fn create_empty_board(rows: usize, cols: usize) -> Board {
-    (0..rows)
-        .map(|_| (0..cols).map(|_| Cell::default()).collect())
+/// Whether the lobby's game has been played to a result
+fn has_result(status: LobbyStatus) -> bool {
+    matches!(status, LobbyStatus::Finished | LobbyStatus::Settling | LobbyStatus::Settled)
+}
+
+// ============================================================================
+// LOBBY VISIBILITY
+// ============================================================================
+
+/// LobbyVisibility - Who can find and join a lobby
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub enum LobbyVisibility {
+    Public,   // Listed, anyone can join
+    Unlisted, // Not listed, anyone with the id or room code can join
+    Private,  // Only invited players or those with the passcode can join
+}
+
+// Passcode length limits (in characters)
+const MIN_PASSCODE_LENGTH: usize = 4;
+const MAX_PASSCODE_LENGTH: usize = 64;
+
+// Longest an invite can hold a seat
+const MAX_INVITE_TTL_MINUTES: u32 = 7 * 24 * 60;
+
+// How long a rematch holds seats for the players of the finished lobby
+const REMATCH_INVITE_TTL_MINUTES: u64 = 15;
+
+/// Whether an invite is addressed to this identity or wallet
+fn invite_targets(invite: &LobbyInvite, identity: Identity, address: &str) -> bool {
+    invite.identity == Some(identity) || invite.address.as_deref() == Some(address.to_lowercase().as_str())
+}
+
+/// Seats held by open invites: one per distinct invitee who is not seated yet,
+/// leaving out `joiner`, whose own invite is the seat they are taking
+fn count_reserved_seats(
+    open_invites: &[LobbyInvite],
+    players: &[LobbyPlayer],
+    joiner: Option<(Identity, &str)>,
+) -> usize {
+    let mut counted: Vec<&LobbyInvite> = Vec::new();
+    for invite in open_invites {
+        let seated = players.iter().any(|p| invite_targets(invite, p.identity, &p.address));
+        let joining = joiner.is_some_and(|(identity, address)| invite_targets(invite, identity, address));
+        let duplicate = counted.iter().any(|c| {
+            (c.identity.is_some() && c.identity == invite.identity)
+                || (c.address.is_some() && c.address == invite.address)
+        });
+        if !seated && !joining && !duplicate {
+            counted.push(invite);
+        }
+    }
+    counted.len()
+}
+
+/// Hash a lobby passcode with its salt (hex-encoded SHA3-256)
+fn hash_passcode(salt: &str, passcode: &str) -> String {
+    let digest = Sha3_256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(b":")
+        .chain_update(passcode.as_bytes())
+        .finalize();
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============================================================================
+// WALLET SIGNATURES (EIP-191 / SIWE)
+// ============================================================================
+
+// How long a sign-in nonce stays valid
+const WALLET_NONCE_TTL_SECS: u64 = 10 * 60;
+
+// Site that sign-in messages must be issued by; their URI must be on it too
+const SIWE_DOMAIN: &str = "onchainreaction.vercel.app";
+
+// Chains a wallet sign-in message may name (Base, Arbitrum)
+const SIWE_CHAIN_IDS: [u32; 2] = [8453, 42161];
+
+// Sign In With Farcaster messages always name OP Mainnet, home of the IdRegistry
+const FARCASTER_CHAIN_ID: u32 = 10;
+
+/// The fields of an EIP-4361 (Sign-In with Ethereum) message we check
+#[derive(Debug, Clone, PartialEq)]
+struct SiweMessage {
+    domain: String,
+    address: String,             // Lowercased
+    uri: String,
+    version: String,
+    chain_id: u32,
+    nonce: String,
+    resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Whether the message was issued by SIWE_DOMAIN for a page on it
+    fn is_for_our_site(&self) -> bool {
+        let origin = format!("https://{}", SIWE_DOMAIN);
+        self.domain == SIWE_DOMAIN
+            && (self.uri == origin || self.uri.starts_with(&format!("{}/", origin)))
+    }
+}
+
+/// Parse an EIP-4361 message: a "<domain> wants you to sign in with your
+/// Ethereum account:" header, the address on the next line, then
+/// "Key: value" fields and an optional "Resources:" list
+fn parse_siwe_message(message: &str) -> Option<SiweMessage> {
+    let mut lines = message.lines();
+    let domain = lines.next()?
+        .strip_suffix(" wants you to sign in with your Ethereum account:")?
+        .to_string();
+    let address = normalize_address(lines.next()?).ok()?;
+
+    let mut uri = None;
+    let mut version = None;
+    let mut chain_id = None;
+    let mut nonce = None;
+    let mut resources = Vec::new();
+    let mut in_resources = false;
+    for line in lines {
+        if in_resources {
+            if let Some(resource) = line.strip_prefix("- ") {
+                resources.push(resource.to_string());
+                continue;
+            }
+            in_resources = false;
+        }
+        if let Some(value) = line.strip_prefix("URI: ") {
+            uri = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("Version: ") {
+            version = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("Chain ID: ") {
+            chain_id = Some(value.parse().ok()?);
+        } else if let Some(value) = line.strip_prefix("Nonce: ") {
+            nonce = Some(value.to_string());
+        } else if line == "Resources:" {
+            in_resources = true;
+        }
+    }
+
+    Some(SiweMessage {
+        domain,
+        address,
+        uri: uri?,
+        version: version?,
+        chain_id: chain_id?,
+        nonce: nonce?,
+        resources,
+    })
+}
+
+/// Lowercase and validate a 0x-prefixed wallet address
+fn normalize_address(address: &str) -> Result<String, ErrorCode> {
+    let address = address.trim().to_lowercase();
+    let is_address = address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit());
+    if !is_address {
+        return Err(ErrorCode::InvalidAddress);
+    }
+    Ok(address)
+}
+
+/// Decode a hex string (with or without 0x prefix)
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.trim().trim_start_matches("0x");
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
         .collect()
 }
 
-fn get_max_capacity(row: usize, col: usize, rows: usize, cols: usize) -> u32 {
-    let is_corner = (row == 0 || row == rows - 1) && (col == 0 || col == cols - 1);
-    let is_edge = row == 0 || row == rows - 1 || col == 0 || col == cols - 1;
+/// Recover the lowercased address that produced an EIP-191 `personal_sign`
+/// signature (65 bytes: r, s, v) over `message`
+fn recover_personal_sign_address(message: &str, signature_hex: &str) -> Option<String> {
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
 
-    if is_corner { 2 } else if is_edge { 3 } else { 4 }
+    let bytes = decode_hex(signature_hex)?;
+    if bytes.len() != 65 {
+        return None;
+    }
+
+    // v is 27/28 in Ethereum signatures, 0/1 in some wallets
+    let v = if bytes[64] >= 27 { bytes[64] - 27 } else { bytes[64] };
+    let mut recovery_id = RecoveryId::from_byte(v)?;
+    let mut signature = Signature::from_slice(&bytes[..64]).ok()?;
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+        recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
+    }
+
+    // "\x19Ethereum Signed Message:\n" + length + message
+    let prehash = Keccak256::new()
+        .chain_update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes())
+        .chain_update(message.as_bytes())
+        .finalize();
+
+    let key = VerifyingKey::recover_from_prehash(&prehash, &signature, recovery_id).ok()?;
+    let public_key = key.to_encoded_point(false);
+    let address_hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+
+    let address: String = address_hash[12..].iter().map(|b| format!("{:02x}", b)).collect();
+    Some(format!("0x{}", address))
 }
 
-// Player colors
-const PLAYER_COLORS: [&str; 8] = ["red", "blue", "green", "yellow", "purple", "orange", "pink", "cyan"];
+// ============================================================================
+// MATCHMAKING CONFIGURATION
+// ============================================================================
+
+// How often the matcher runs
+const MATCHMAKER_INTERVAL_SECS: u64 = 2;
+
+// How long a matched lobby waits for its match id and deposits before it is cancelled
+const MATCHED_DEPOSIT_WINDOW_SECS: u64 = 5 * 60;
+
+// Rating used until a player has a rating of their own
+const DEFAULT_RATING: i32 = 1500;
+
+// Rating band: starts at +/-100 and widens by 50 every 10 seconds of waiting, up to +/-1000
+const RATING_BAND_BASE: i32 = 100;
+const RATING_BAND_STEP: i32 = 50;
+const RATING_BAND_STEP_SECS: u64 = 10;
+const RATING_BAND_MAX: i32 = 1000;
+
+/// How far from their own rating a ticket will accept opponents after waiting
+fn rating_band(waited: std::time::Duration) -> i32 {
+    let steps = (waited.as_secs() / RATING_BAND_STEP_SECS) as i32;
+    (RATING_BAND_BASE + steps.saturating_mul(RATING_BAND_STEP)).min(RATING_BAND_MAX)
+}
 
 // ============================================================================
-// HELPER FUNCTIONS
+// PAYOUT CONFIGURATION
 // ============================================================================
 
-fn generate_id() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    format!("{:x}", time)
+// Cut of the pool the arena contract keeps when the winner claims. Mirrors
+// feeBps in OnchainReactionBase; change both together.
+const CONTRACT_FEE_BPS: u32 = 50;
+
+// Largest entry fee a lobby, queue or tournament may charge
+// (1,000,000 USDC in 6-decimal base units)
+const MAX_ENTRY_FEE: u128 = 1_000_000_000_000;
+
+/// Parse a decimal token amount (e.g. USDC wei units) into a U256
+fn parse_amount(amount: &str) -> Result<u256, ErrorCode> {
+    let digits = amount.trim();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ErrorCode::InvalidAmount);
+    }
+    digits.parse::<u256>().map_err(|_| ErrorCode::InvalidAmount)
+}
+
+/// Parse an entry fee, capped at MAX_ENTRY_FEE so pool maths can't overflow
+fn parse_entry_fee(amount: &str) -> Result<u256, ErrorCode> {
+    let fee = parse_amount(amount)?;
+    if fee > u256::from(MAX_ENTRY_FEE) {
+        return Err(ErrorCode::EntryFeeTooHigh);
+    }
+    Ok(fee)
+}
+
+/// What the winner of a paid match actually receives: every entry fee, less
+/// the contract's cut. None on overflow.
+fn net_prize_pool(entry_fee: u256, players: u32) -> Option<u256> {
+    let pool = entry_fee.checked_mul(u256::from(players))?;
+    let fee = pool.checked_mul(u256::from(CONTRACT_FEE_BPS))? / u256::from(10_000u32);
+    pool.checked_sub(fee)
 }
 
 // ============================================================================
-// LIFECYCLE REDUCERS
+// RATING CONFIGURATION
 // ============================================================================
 
-#[spacetimedb::reducer(init)]
-pub fn init(_ctx: &ReducerContext) {
-    log::info!("Chain Reaction module initialized!");
+// Elo K-factors: new players move faster until they have played enough games
+const RATING_K_PROVISIONAL: f64 = 40.0;
+const RATING_K_ESTABLISHED: f64 = 20.0;
+const PROVISIONAL_GAMES: u32 = 10;
+
+/// Name of the rating pool a time control belongs to
+fn variant_name(time_control: TimeControl) -> &'static str {
+    match time_control {
+        TimeControl::Blitz => "blitz",
+        TimeControl::Standard => "standard",
+        TimeControl::Correspondence(_) => "correspondence",
+        TimeControl::Clock(_) => "clock",
+    }
 }
 
-#[spacetimedb::reducer(client_connected)]
-pub fn identity_connected(ctx: &ReducerContext) {
-    log::info!("Client connected: {:?}", ctx.sender);
+/// Key a player's ratings by wallet address, falling back to identity
+fn player_key(identity: Identity, address: &str) -> String {
+    if address.is_empty() {
+        identity.to_hex().to_string()
+    } else {
+        address.to_lowercase()
+    }
 }
 
-#[spacetimedb::reducer(client_disconnected)]
-pub fn identity_disconnected(ctx: &ReducerContext) {
-    log::info!("Client disconnected: {:?}", ctx.sender);
+fn rating_id(player_key: &str, max_players: u32, variant: &str) -> String {
+    format!("{}_{}_{}", player_key, max_players, variant)
 }
 
 // ============================================================================
-// GAME REDUCERS
+// TOURNAMENT CONFIGURATION
 // ============================================================================
 
-/// Create a new lobby
-#[spacetimedb::reducer]
-pub fn create_lobby(
-    ctx: &ReducerContext,
-    chain_id: u32,
-    match_id: u64,
-    arena_address: String,
-    host_address: String,
-    entry_fee: String,
-    max_players: u32,
-    host_name: String,
-    lobby_id: String,
-) {
-    let now = ctx.timestamp;
+// Entry limits for a single tournament
+const MIN_TOURNAMENT_ENTRIES: u32 = 2;
+const MAX_TOURNAMENT_ENTRIES: u32 = 256;
 
-    // Create lobby
-    ctx.db.lobby().insert(Lobby {
-        id: lobby_id.clone(),
-        chain_id,
-        match_id,
-        arena_address,
-        host_identity: ctx.sender,
-        host_address: host_address.clone(),
-        entry_fee,
-        max_players,
-        status: "waiting".to_string(),
-        winner_identity: None,
-        winner_address: None,
-        created_at: now,
-        updated_at: now,
-    });
+// Longest a round may stay open before unplayed matches can be forfeited
+const MAX_ROUND_DEADLINE_MINUTES: u32 = 7 * 24 * 60;
 
-    // Add host as first player
-    let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
-    ctx.db.lobby_player().insert(LobbyPlayer {
-        id: player_id,
-        lobby_id: lobby_id.clone(),
-        identity: ctx.sender,
-        address: host_address,
-        name: host_name,
-        color: PLAYER_COLORS[0].to_string(),
-        is_host: true,
-        is_alive: true,
-        has_deposited: true, // Host pays on creation
-        joined_at: now,
-    });
+// Most rounds a Swiss tournament can be set to play
+const MAX_SWISS_ROUNDS: u32 = 15;
 
-    // Calculate board size based on max players
-    let (rows, cols) = get_board_size(max_players);
-    let board = create_empty_board(rows, cols);
-    
-    // Create empty game state with board dimensions
-    ctx.db.game_state().insert(GameState {
-        lobby_id: lobby_id.clone(),
-        board_json: serde_json::to_string(&board).unwrap(),
-        rows: rows as u32,
-        cols: cols as u32,
-        current_player_index: 0,
-        move_count: 0,
-        last_move_at: now,
-        turn_deadline: now, // Will be set correctly on start_game
-        turn_lock_until: None,
-        last_move_player: None,
-    });
+// Pairing attempts before a Swiss round gives up on avoiding every rematch
+const SWISS_PAIRING_BUDGET: u32 = 10_000;
+
+/// TournamentFormat - How a tournament's rounds are drawn
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub enum TournamentFormat {
+    Knockout,             // Bracket (2 per match) or heats (3-5); losers are out
+    Swiss(SwissSettings), // Everyone plays every round, paired by score
+}
+
+/// SwissSettings - Round count and tiebreak for a Swiss tournament
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub struct SwissSettings {
+    pub rounds: u32,
+    pub tiebreak: Tiebreak, // Applied first; the other breaks any remaining tie
+}
+
+/// Tiebreak - Secondary ordering for players level on score
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq)]
+pub enum Tiebreak {
+    Buchholz,        // Sum of opponents' scores
+    SonnebornBerger, // Sum of the scores of opponents beaten
+}
+
+/// Pair `players` (ordered best first) into twos without repeating any pair
+/// in `played`, preferring partners close in the order. Returns None if no
+/// such pairing is found within the search budget.
+fn pair_without_rematches(
+    players: &[Identity],
+    played: &std::collections::HashSet<(Identity, Identity)>,
+    budget: &mut u32,
+) -> Option<Vec<(Identity, Identity)>> {
+    let Some((&first, rest)) = players.split_first() else {
+        return Some(Vec::new());
+    };
+
+    for (i, &opponent) in rest.iter().enumerate() {
+        if *budget == 0 {
+            return None;
+        }
+        *budget -= 1;
+        if played.contains(&(first, opponent)) {
+            continue;
+        }
+
+        let remaining: Vec<_> = rest.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, p)| *p)
+            .collect();
+        if let Some(mut pairs) = pair_without_rematches(&remaining, played, budget) {
+            pairs.insert(0, (first, opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// Seed at each position of a single-elimination bracket with `size` slots
+/// (a power of two), laid out so the top two seeds can only meet in the final
+fn bracket_positions(size: u32) -> Vec<u32> {
+    let mut positions = vec![1];
+    while (positions.len() as u32) < size {
+        let mirror = positions.len() as u32 * 2 + 1;
+        positions = positions.iter().flat_map(|&seed| [seed, mirror - seed]).collect();
+    }
+    positions
+}
+
+/// Groups for the next knockout round from each slot's winner, in slot order.
+/// Slots 2k and 2k+1 feed slot k, so the bracket keeps its shape: a winner
+/// whose neighbour has none gets a bye, and a pair with no winner leaves an
+/// empty slot.
+fn next_bracket_round<T>(winners: Vec<Option<T>>) -> Vec<Vec<T>> {
+    let mut winners = winners.into_iter();
+    let mut groups = Vec::new();
+    while let Some(first) = winners.next() {
+        let second = winners.next().flatten();
+        groups.push(first.into_iter().chain(second).collect());
+    }
+    groups
+}
+
+/// Deal seeded players into as few heats of at most `per_heat` as possible,
+/// snaking the seeds (1-2-3, 3-2-1, ...) so the heats come out balanced
+fn snake_heats<T: Clone>(seeded: &[T], per_heat: usize) -> Vec<Vec<T>> {
+    let heat_count = seeded.len().div_ceil(per_heat).max(1);
+    let mut heats = vec![Vec::new(); heat_count];
+    for (i, item) in seeded.iter().enumerate() {
+        let col = i % heat_count;
+        let heat = if (i / heat_count).is_multiple_of(2) { col } else { heat_count - 1 - col };
+        heats[heat].push(item.clone());
+    }
+    heats
+}
+
+// ============================================================================
+// ACHIEVEMENT CONFIGURATION
+// ============================================================================
+
+// Achievement definitions: (id, name, description, target progress).
+// Seeded into the achievement table; ids are stable and must never change.
+const ACHIEVEMENTS: [(&str, &str, &str, u32); 4] = [
+    ("chain_reaction", "Chain Reaction", "Set off 10 explosions with a single move", 10),
+    ("flawless", "Flawless", "Win a game without losing a cell", 1),
+    ("comeback", "Comeback", "Win a game after being down to a single cell", 1),
+    ("five_way_victor", "Five-Way Victor", "Win 5 five-player games", 5),
+];
+
+// ============================================================================
+// AUDIT CONFIGURATION
+// ============================================================================
+
+// Audit events older than this are pruned
+const AUDIT_RETENTION_DAYS: u64 = 30;
+
+// How often the pruner runs
+const AUDIT_PRUNE_INTERVAL_SECS: u64 = 60 * 60;
+
+// ============================================================================
+// CHAT CONFIGURATION
+// ============================================================================
+
+// Longest chat message accepted (in characters)
+const MAX_CHAT_LENGTH: usize = 280;
+
+// Rate limit: at most CHAT_RATE_LIMIT messages per CHAT_RATE_WINDOW_SECS per identity
+const CHAT_RATE_LIMIT: u32 = 5;
+const CHAT_RATE_WINDOW_SECS: u64 = 10;
+
+// Words masked out of chat messages (matched case-insensitively on whole words)
+const BLOCKED_WORDS: [&str; 6] = ["fuck", "shit", "cunt", "bitch", "nigger", "faggot"];
+
+/// Mask blocked words with asterisks, keeping everything else intact
+fn filter_chat_text(text: &str) -> String {
+    let mut filtered = String::with_capacity(text.len());
+    let mut word = String::new();
+
+    let flush = |word: &mut String, out: &mut String| {
+        if BLOCKED_WORDS.contains(&word.to_lowercase().as_str()) {
+            out.extend(std::iter::repeat_n('*', word.chars().count()));
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    };
+
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            word.push(ch);
+        } else {
+            flush(&mut word, &mut filtered);
+            filtered.push(ch);
+        }
+    }
+    flush(&mut word, &mut filtered);
+
+    filtered
+}
+
+// ============================================================================
+// TABLES
+// ============================================================================
+
+/// Lobby - Represents a match room
+#[spacetimedb::table(name = lobby, public)]
+pub struct Lobby {
+    #[primary_key]
+    pub id: String,              // Generated by the module
+    #[unique]
+    pub room_code: String,       // Short shareable code, e.g. "AB3K"
+    pub chain_id: u32,           // 8453 (Base) or 42161 (Arbitrum)
+    pub match_id: u64,           // On-chain match ID
+    pub arena_address: String,   // ChainOrbArena contract address
+    pub host_identity: Identity,
+    pub host_address: String,    // Wallet address (may differ from the on-chain creator after migration)
+    pub entry_fee: u256,         // In USDC wei units
+    pub max_players: u32,
+    pub status: LobbyStatus,     // Only changed through transition_lobby
+    pub time_control: TimeControl,
+    pub is_locked: bool,         // Host has closed the lobby to new joins
+    pub visibility: LobbyVisibility,
+    pub has_passcode: bool,      // Hash lives in the private lobby_passcode table
+    pub allow_spectators: bool,
+    pub max_spectators: Option<u32>, // None = unlimited
+    pub spectator_count: u32,
+    pub previous_lobby_id: Option<String>, // Set when this lobby is a rematch
+    pub rematch_lobby_id: Option<String>,  // Set once a rematch has been requested
+    pub tournament_id: Option<String>,     // Set when the lobby is a tournament match
+    pub winner_identity: Option<Identity>,
+    pub winner_address: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// LobbyTransition - Every status change a lobby has gone through
+#[spacetimedb::table(name = lobby_transition, public)]
+pub struct LobbyTransition {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub lobby_id: String,
+    pub from_status: Option<LobbyStatus>, // None when the lobby was created
+    pub to_status: LobbyStatus,
+    pub reason: String,          // e.g. "created" | "game_started" | "resignation" | "payouts_executed"
+    pub sender: Identity,        // Caller of the reducer that caused it
+    pub at: Timestamp,
+}
+
+/// LastCreatedLobby - The latest lobby each identity created, so the client
+/// can learn the id and room code create_lobby assigned
+#[spacetimedb::table(name = last_created_lobby, public)]
+pub struct LastCreatedLobby {
+    #[primary_key]
+    pub identity: Identity,
+    pub lobby_id: String,
+    pub room_code: String,
+    pub created_at: Timestamp,
+}
+
+/// LobbyPlayer - Players in a lobby
+#[spacetimedb::table(name = lobby_player, public)]
+#[derive(Clone)]
+pub struct LobbyPlayer {
+    #[primary_key]
+    pub id: String,              // lobbyId + "_" + identity hex
+    #[index(btree)]
+    pub lobby_id: String,
+    pub identity: Identity,
+    pub address: String,         // Wallet address
+    pub name: String,
+    pub color: String,           // "red" | "blue" | "green" | "yellow" | etc.
+    pub is_host: bool,
+    pub is_alive: bool,
+    pub has_deposited: bool,
+    pub is_ready: bool,
+    pub time_bank_ms: u64,       // Remaining chess-clock time (Clock time control only)
+    pub eliminated_at_move: Option<u32>, // Move index at which the player was knocked out
+    pub fid: Option<u64>,        // Linked Farcaster FID, if any
+    pub joined_at: Timestamp,
+}
+
+/// Settlement - Payout owed to a placement in a finished lobby, executed by the oracle
+#[spacetimedb::table(name = settlement, public)]
+pub struct Settlement {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub lobby_id: String,
+    pub chain_id: u32,
+    pub match_id: u64,           // On-chain match ID
+    pub arena_address: String,
+    pub placement: u32,
+    pub identity: Identity,
+    pub address: String,         // Wallet address to pay
+    pub amount: u256,            // In USDC wei units
+    pub status: String,          // "pending" | "executed"
+    pub tx_hash: Option<String>,
+    pub created_at: Timestamp,
+    pub executed_at: Option<Timestamp>,
+}
+
+/// Oracle - Identities allowed to mark settlements as executed on-chain
+#[spacetimedb::table(name = oracle, public)]
+pub struct Oracle {
+    #[primary_key]
+    pub identity: Identity,
+    pub added_by: Identity,
+    pub added_at: Timestamp,
+}
+
+/// Standing - Final placement of each player in a match
+#[spacetimedb::table(name = standing, public)]
+pub struct Standing {
+    #[primary_key]
+    pub id: String,              // lobbyId + "_" + identity hex (same as LobbyPlayer)
+    #[index(btree)]
+    pub lobby_id: String,
+    pub identity: Identity,
+    pub address: String,
+    pub name: String,
+    pub fid: Option<u64>,        // Linked Farcaster FID, if any
+    pub placement: u32,          // 1 = winner; 0 until the game ends
+    pub eliminated_at_move: Option<u32>, // None for the winner
+    pub cause: String,           // "winner" | "orbs_lost" | "timeout" | "resign"
+    pub final_orbs: u32,
+    pub recorded_at: Timestamp,
+}
+
+/// PlayerRating - Skill rating per player, player count and variant
+#[spacetimedb::table(name = player_rating, public)]
+pub struct PlayerRating {
+    #[primary_key]
+    pub id: String,              // playerKey + "_" + maxPlayers + "_" + variant
+    #[index(btree)]
+    pub player_key: String,      // Lowercased wallet address (identity hex if none)
+    pub identity: Identity,
+    pub max_players: u32,
+    pub variant: String,         // "blitz" | "standard" | "correspondence" | "clock"
+    pub rating: i32,
+    pub games_played: u32,
+    pub wins: u32,
+    pub provisional: bool,       // True until PROVISIONAL_GAMES games are played
+    pub updated_at: Timestamp,
+}
+
+/// Season - Leaderboard period; exactly one is active at a time
+#[spacetimedb::table(name = season, public)]
+pub struct Season {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u32,
+    pub name: String,
+    pub is_active: bool,
+    pub started_at: Timestamp,
+    pub ended_at: Option<Timestamp>,
+}
+
+/// LeaderboardEntry - A player's running totals on one leaderboard
+/// (season x chain x player count x variant), updated as lobbies finish
+#[spacetimedb::table(
+    name = leaderboard_entry,
+    public,
+    index(name = board, btree(columns = [season_id, chain_id, max_players, variant]))
+)]
+pub struct LeaderboardEntry {
+    #[primary_key]
+    pub id: String,              // season_chain_maxPlayers_variant_playerKey
+    pub season_id: u32,
+    pub chain_id: u32,
+    pub max_players: u32,
+    pub variant: String,         // See variant_name()
+    pub player_key: String,      // Same key as PlayerRating
+    pub identity: Identity,
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub earnings: u256,          // Total payouts won, in USDC wei units
+    pub rating: i32,             // Latest rating in the matching rating pool
+    pub updated_at: Timestamp,
+}
+
+/// SeasonArchive - Final leaderboard positions, written at season rollover
+#[spacetimedb::table(name = season_archive, public)]
+pub struct SeasonArchive {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub season_id: u32,
+    pub chain_id: u32,
+    pub max_players: u32,
+    pub variant: String,
+    pub player_key: String,
+    pub identity: Identity,
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub earnings: u256,
+    pub rating: i32,
+    pub wins_rank: u32,
+    pub earnings_rank: u32,
+    pub rating_rank: u32,
+}
+
+/// MatchmakingTicket - A player waiting to be matched into a lobby
+#[spacetimedb::table(name = matchmaking_ticket, public)]
+#[derive(Clone)]
+pub struct MatchmakingTicket {
+    #[primary_key]
+    pub identity: Identity,      // One ticket per identity
+    pub address: String,         // Wallet address
+    pub name: String,
+    pub chain_id: u32,
+    pub arena_address: String,   // Lowercased
+    pub entry_fee: u256,         // In USDC wei units; only identical fees are grouped
+    pub max_players: u32,
+    pub rating: i32,
+    pub status: String,          // "searching" | "matched"
+    pub lobby_id: Option<String>, // Set once matched; the player should now deposit
+    pub created_at: Timestamp,
+}
+
+/// MatchmakingSchedule - Drives the periodic matcher
+#[spacetimedb::table(name = matchmaking_schedule, scheduled(run_matchmaker))]
+pub struct MatchmakingSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+/// DepositDeadline - Cancels a matched lobby that never got going
+#[spacetimedb::table(name = deposit_deadline, scheduled(expire_matched_lobby))]
+pub struct DepositDeadline {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+    pub lobby_id: String,
+}
+
+/// LobbyPasscode - Salted passcode hashes, kept out of the public lobby table
+#[spacetimedb::table(name = lobby_passcode)]
+pub struct LobbyPasscode {
+    #[primary_key]
+    pub lobby_id: String,
+    pub salt: String,
+    pub passcode_hash: String,
+}
+
+/// LobbyInvite - Seats reserved for specific players in a lobby
+#[spacetimedb::table(name = lobby_invite, public)]
+#[derive(Clone)]
+pub struct LobbyInvite {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub lobby_id: String,
+    pub address: Option<String>,      // Lowercased wallet address
+    pub identity: Option<Identity>,
+    pub invited_by: Identity,
+    pub created_at: Timestamp,
+    pub expires_at: Timestamp,        // Seat is released after this
+    pub claimed_by: Option<Identity>,
+}
+
+/// Profile - Persistent player details: wallet, display name, avatar and preferences
+#[spacetimedb::table(name = profile, public)]
+pub struct Profile {
+    #[primary_key]
+    pub identity: Identity,
+    #[index(btree)]
+    pub primary_address: String,  // Lowercased wallet address used in lobbies
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub preferred_color: Option<String>, // One of PLAYER_COLORS
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// WalletNonce - Single-use nonce the caller must sign to link a wallet
+#[spacetimedb::table(name = wallet_nonce, public)]
+pub struct WalletNonce {
+    #[primary_key]
+    pub identity: Identity,
+    pub nonce: String,
+    pub issued_at: Timestamp,
+    pub expires_at: Timestamp,
+}
+
+/// WalletLink - Wallet addresses proven to belong to an identity
+#[spacetimedb::table(name = wallet_link, public)]
+pub struct WalletLink {
+    #[primary_key]
+    pub address: String,          // Lowercased wallet address
+    #[index(btree)]
+    pub identity: Identity,
+    pub linked_at: Timestamp,
+}
+
+/// FarcasterLink - Verified Farcaster account for an identity
+#[spacetimedb::table(name = farcaster_link, public)]
+pub struct FarcasterLink {
+    #[primary_key]
+    pub identity: Identity,
+    #[unique]
+    pub fid: u64,
+    pub username: String,
+    pub pfp_url: Option<String>,
+    pub custody_address: Option<String>, // Signer of the sign-in message (signature links only)
+    pub verified_by: String,      // "signature" | "oracle"
+    pub linked_at: Timestamp,
+}
+
+/// FarcasterAccount - Custody address and profile of an FID as read from the
+/// IdRegistry and hubs by the oracle. Sign-in messages are only trusted when
+/// they are signed by the custody address recorded here.
+#[spacetimedb::table(name = farcaster_account, public)]
+pub struct FarcasterAccount {
+    #[primary_key]
+    pub fid: u64,
+    pub custody_address: String, // Lowercased
+    pub username: String,
+    pub pfp_url: Option<String>,
+    pub updated_at: Timestamp,
+}
+
+/// Refund - Deposits owed back to players removed from a lobby
+#[spacetimedb::table(name = refund, public)]
+pub struct Refund {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub lobby_id: String,
+    pub chain_id: u32,
+    pub match_id: u64,           // On-chain match the deposit was made to
+    pub identity: Identity,
+    pub address: String,         // Wallet address to refund
+    pub amount: u256,            // In USDC wei units
+    pub reason: String,          // "kicked" | "left" | "round_timeout" | "deposit_timeout"
+    pub processed: bool,
+    pub tx_hash: Option<String>,
+    pub created_at: Timestamp,
+    pub processed_at: Option<Timestamp>,
+}
+
+/// Tournament - A cup whose rounds are played out in generated lobbies
+#[spacetimedb::table(name = tournament, public)]
+pub struct Tournament {
+    #[primary_key]
+    pub id: String,              // Generated by the module
+    pub name: String,
+    pub organizer: Identity,
+    pub chain_id: u32,
+    pub arena_address: String,   // ChainOrbArena contract the match lobbies use
+    pub entry_fee: u256,         // Per match, in USDC wei units
+    pub format: TournamentFormat,
+    pub players_per_match: u32,  // Knockout: 2 = bracket, 3-5 = heats. Swiss: always 2
+    pub time_control: TimeControl,
+    pub max_entries: u32,
+    pub status: String,          // "registration" | "running" | "finished" | "cancelled"
+    pub current_round: u32,      // 0 until the tournament starts
+    pub round_deadline_minutes: u32,
+    pub round_deadline: Option<Timestamp>, // Unplayed matches can be forfeited after this
+    pub winner_identity: Option<Identity>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// TournamentEntry - A player registered for a tournament
+#[spacetimedb::table(name = tournament_entry, public)]
+#[derive(Clone)]
+pub struct TournamentEntry {
+    #[primary_key]
+    pub id: String,              // tournamentId + "_" + identity hex
+    #[index(btree)]
+    pub tournament_id: String,
+    pub identity: Identity,
+    pub address: String,         // Wallet address
+    pub name: String,
+    pub rating: i32,             // Rating at registration, used for seeding
+    pub seed: u32,               // 1 = top seed; 0 until the tournament starts
+    pub status: String,          // "active" | "eliminated" | "winner"
+    pub eliminated_in_round: Option<u32>, // Knockout only
+    pub score: u32,              // Swiss: wins plus byes
+    pub buchholz: u32,           // Swiss: sum of opponents' scores
+    pub sonneborn_berger: u32,   // Swiss: sum of beaten opponents' scores
+    pub rank: u32,               // Swiss: current standing; 0 until a round is played
+    pub joined_at: Timestamp,
+}
+
+/// TournamentMatch - One bracket match or heat of a tournament round
+#[spacetimedb::table(name = tournament_match, public)]
+pub struct TournamentMatch {
+    #[primary_key]
+    pub id: String,              // tournamentId + "_" + round + "_" + slot
+    #[index(btree)]
+    pub tournament_id: String,
+    pub round: u32,
+    pub slot: u32,               // Winners of neighbouring slots meet next round
+    pub lobby_id: Option<String>, // None for a bye or an empty slot
+    pub players: Vec<Identity>,  // Best seed first
+    pub winner_identity: Option<Identity>, // None if nobody showed up
+    pub status: String,          // "live" | "finished" | "bye" | "forfeit"
+}
+
+/// Achievement - Definition of an achievement players can earn
+#[spacetimedb::table(name = achievement, public)]
+pub struct Achievement {
+    #[primary_key]
+    pub id: String,              // Stable key, e.g. "chain_reaction"
+    pub name: String,
+    pub description: String,
+    pub target: u32,             // Progress needed to unlock
+}
+
+/// PlayerAchievement - A player's progress toward an achievement
+#[spacetimedb::table(name = player_achievement, public)]
+pub struct PlayerAchievement {
+    #[primary_key]
+    pub id: String,              // identity hex + "_" + achievementId
+    #[index(btree)]
+    pub identity: Identity,
+    pub achievement_id: String,
+    pub progress: u32,           // Never exceeds the achievement's target
+    pub unlocked_at: Option<Timestamp>,
+    pub updated_at: Timestamp,
+}
+
+/// AchievementTracker - Per-game facts about a player that achievements depend on
+#[spacetimedb::table(name = achievement_tracker)]
+pub struct AchievementTracker {
+    #[primary_key]
+    pub id: String,              // Same as the lobby player id
+    #[index(btree)]
+    pub lobby_id: String,
+    pub peak_cells: u32,
+    pub lost_cell: bool,         // An opponent has captured one of the player's cells
+    pub fell_to_one_cell: bool,  // Dropped back to a single cell after holding more
+}
+
+/// MatchStats - A player's statistics for one game, for the post-game summary
+#[spacetimedb::table(name = match_stats, public)]
+pub struct MatchStats {
+    #[primary_key]
+    pub id: String,              // Same as the lobby player id
+    #[index(btree)]
+    pub lobby_id: String,
+    pub identity: Identity,
+    pub moves_made: u32,
+    pub explosions: u32,         // Total explosions set off by the player's moves
+    pub longest_chain: u32,      // Most explosions set off by a single move
+    pub cells_captured: u32,     // Opponent cells taken over
+    pub peak_orbs: u32,
+    pub total_think_ms: u64,
+    pub avg_think_ms: u64,       // Filled in when the game ends
+    pub is_final: bool,          // Set once the game has ended
+    pub updated_at: Timestamp,
+}
+
+/// AuditEvent - Append-only record of accepted game, account and admin
+/// reducer calls (see audit)
+#[spacetimedb::table(name = audit_event, public)]
+pub struct AuditEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub timestamp: Timestamp,
+    #[index(btree)]
+    pub timestamp_micros: i64,   // Same instant, indexed so pruning can delete by range
+    #[index(btree)]
+    pub sender: Identity,
+    pub reducer: String,
+    pub lobby_id: Option<String>,
+    pub payload: String,         // JSON object of the call's arguments
+    pub outcome: String,         // What the call did, e.g. "ok" | "finished" | "cancelled"
+}
+
+/// AuditPruneSchedule - Drives the periodic audit retention sweep
+#[spacetimedb::table(name = audit_prune_schedule, scheduled(prune_audit_events))]
+pub struct AuditPruneSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+/// Spectator - Clients watching a lobby without playing
+#[spacetimedb::table(name = spectator, public)]
+pub struct Spectator {
+    #[primary_key]
+    pub id: String,              // lobbyId + "_" + identity hex
+    #[index(btree)]
+    pub lobby_id: String,
+    #[index(btree)]
+    pub identity: Identity,
+    pub joined_at: Timestamp,
+}
+
+/// GameState - Current board state for a lobby
+#[spacetimedb::table(name = game_state, public)]
+pub struct GameState {
+    #[primary_key]
+    pub lobby_id: String,
+    pub board_json: String,       // JSON serialized board
+    pub rows: u32,                // Board height
+    pub cols: u32,                // Board width
+    pub current_player_index: u32,
+    pub move_count: u32,
+    pub last_move_at: Timestamp,
+    pub turn_deadline: Timestamp, // When the current turn expires
+    pub turn_lock_until: Option<Timestamp>, // Turn lock to prevent race conditions
+    pub last_move_player: Option<Identity>, // Last player who made a move
+}
+
+/// GameMove - Individual moves for replay/verification
+#[spacetimedb::table(name = game_move, public)]
+pub struct GameMove {
+    #[primary_key]
+    pub id: String,               // lobbyId + "_" + moveIndex
+    #[index(btree)]
+    pub lobby_id: String,
+    pub move_index: u32,
+    pub player_identity: Identity,
+    pub row: u32,
+    pub col: u32,
+    pub timestamp: Timestamp,
+}
+
+/// ChatMessage - Messages sent in a lobby by players and spectators
+#[spacetimedb::table(name = chat_message, public)]
+pub struct ChatMessage {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub lobby_id: String,
+    pub sender: Identity,
+    pub sender_name: String,
+    pub text: String,             // Already passed through the word filter
+    pub sent_at: Timestamp,
+}
+
+/// ChatRateLimit - Per-identity message counter for the current rate window
+#[spacetimedb::table(name = chat_rate_limit)]
+pub struct ChatRateLimit {
+    #[primary_key]
+    pub identity: Identity,
+    pub window_start: Timestamp,
+    pub message_count: u32,
+}
+
+/// ChatMute - Identities blocked from sending chat
+#[spacetimedb::table(name = chat_mute, public)]
+pub struct ChatMute {
+    #[primary_key]
+    pub identity: Identity,
+    pub muted_by: Identity,
+    pub muted_until: Option<Timestamp>, // None = muted until lifted
+    pub created_at: Timestamp,
+}
+
+/// Moderator - Identities allowed to delete messages and mute users
+#[spacetimedb::table(name = moderator, public)]
+pub struct Moderator {
+    #[primary_key]
+    pub identity: Identity,
+    pub added_by: Identity,
+    pub added_at: Timestamp,
+}
+
+/// ErrorCodeInfo - Every code a rejected reducer call can return, so clients
+/// can map "CODE: message" errors back to an ErrorCode
+#[spacetimedb::table(name = error_code, public)]
+pub struct ErrorCodeInfo {
+    #[primary_key]
+    pub code: String,            // e.g. "LOBBY_FULL"
+    pub kind: ErrorCode,
+    pub message: String,
+}
+
+// ============================================================================
+// BOARD TYPES
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Cell {
+    pub orbs: u32,
+    pub owner: Option<String>,
+}
+
+type Board = Vec<Vec<Cell>>;
+
+fn create_empty_board(rows: usize, cols: usize) -> Board {
+    (0..rows)
+        .map(|_| (0..cols).map(|_| Cell::default()).collect())
+        .collect()
+}
+
+/// Total orbs a colour has on the board
+fn count_orbs(board: &Board, color: &str) -> u32 {
+    board.iter()
+        .flatten()
+        .filter(|cell| cell.owner.as_deref() == Some(color))
+        .map(|cell| cell.orbs)
+        .sum()
+}
+
+/// Number of cells a colour owns
+fn count_cells(board: &Board, color: &str) -> u32 {
+    board.iter()
+        .flatten()
+        .filter(|cell| cell.owner.as_deref() == Some(color))
+        .count() as u32
+}
+
+fn get_max_capacity(row: usize, col: usize, rows: usize, cols: usize) -> u32 {
+    let is_corner = (row == 0 || row == rows - 1) && (col == 0 || col == cols - 1);
+    let is_edge = row == 0 || row == rows - 1 || col == 0 || col == cols - 1;
+
+    if is_corner { 2 } else if is_edge { 3 } else { 4 }
+}
+
+/// What resolving one move's chain reaction did to the board
+struct ChainReaction {
+    explosions: u32,
+    cells_captured: u32, // Distinct cells taken from other colours
+}
+
+/// Explode every over-capacity cell until the board settles
+fn run_chain_reaction(board: &mut Board, rows: usize, cols: usize) -> ChainReaction {
+    let mut explosions = 0;
+    let mut captured = std::collections::HashSet::new();
+
+    loop {
+        let mut had_explosion = false;
+
+        for row_idx in 0..rows {
+            for col_idx in 0..cols {
+                let max_cap = get_max_capacity(row_idx, col_idx, rows, cols);
+                if board[row_idx][col_idx].orbs >= max_cap {
+                    had_explosion = true;
+                    explosions += 1;
+
+                    let exploding_owner = board[row_idx][col_idx].owner.clone();
+                    board[row_idx][col_idx].orbs = 0;
+                    board[row_idx][col_idx].owner = None;
+
+                    // Spread to neighbors
+                    let neighbors: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+                    for (dr, dc) in neighbors {
+                        let nr = row_idx as i32 + dr;
+                        let nc = col_idx as i32 + dc;
+                        if nr >= 0 && nr < rows as i32 && nc >= 0 && nc < cols as i32 {
+                            let nr = nr as usize;
+                            let nc = nc as usize;
+                            if board[nr][nc].owner.is_some() && board[nr][nc].owner != exploding_owner {
+                                captured.insert((nr, nc));
+                            }
+                            board[nr][nc].orbs += 1;
+                            board[nr][nc].owner = exploding_owner.clone();
+                        }
+                    }
+                }
+            }
+        }
+
+        if !had_explosion {
+            break;
+        }
+    }
+
+    ChainReaction {
+        explosions,
+        cells_captured: captured.len() as u32,
+    }
+}
+
+// Room codes: same alphabet as lib/roomCode.ts (no 0, O, I, 1), 4-8 characters
+const ROOM_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const ROOM_CODE_MIN_LEN: usize = 4;
+const ROOM_CODE_MAX_LEN: usize = 8;
+const ROOM_CODE_ATTEMPTS_PER_LEN: u32 = 8;
+
+// Profile limits
+const MAX_DISPLAY_NAME_LENGTH: usize = 32;
+const MAX_AVATAR_URL_LENGTH: usize = 512;
+
+// Player colors
+const PLAYER_COLORS: [&str; 8] = ["red", "blue", "green", "yellow", "purple", "orange", "pink", "cyan"];
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Generate an unused lobby id from the reducer's seeded RNG
+fn generate_id(ctx: &ReducerContext) -> String {
+    loop {
+        let id = format!("{:016x}", ctx.rng().gen::<u64>());
+        if ctx.db.lobby().id().find(&id).is_none() {
+            return id;
+        }
+    }
+}
+
+/// Generate an unused room code in the style of lib/roomCode.ts.
+/// Starts at 4 characters and grows when the shorter codes keep colliding.
+fn generate_room_code(ctx: &ReducerContext) -> Result<String, ErrorCode> {
+    for len in ROOM_CODE_MIN_LEN..=ROOM_CODE_MAX_LEN {
+        for _ in 0..ROOM_CODE_ATTEMPTS_PER_LEN {
+            let code: String = (0..len)
+                .map(|_| ROOM_CODE_CHARS[ctx.rng().gen_range(0..ROOM_CODE_CHARS.len())] as char)
+                .collect();
+            if ctx.db.lobby().room_code().find(&code).is_none() {
+                return Ok(code);
+            }
+        }
+    }
+    Err(ErrorCode::RoomCodesExhausted)
+}
+
+/// Pick a colour for a new player: their profile's preferred colour if it is
+/// free, otherwise the first unused entry in PLAYER_COLORS
+fn assign_color(
+    ctx: &ReducerContext,
+    identity: Identity,
+    used_colors: &std::collections::HashSet<String>,
+) -> Result<String, ErrorCode> {
+    let preferred = ctx.db.profile().identity().find(identity)
+        .and_then(|profile| profile.preferred_color)
+        .filter(|color| !used_colors.contains(color));
+
+    match preferred {
+        Some(color) => Ok(color),
+        None => PLAYER_COLORS.iter()
+            .find(|c| !used_colors.contains(**c))
+            .map(|c| c.to_string())
+            .ok_or(ErrorCode::NoColoursAvailable),
+    }
+}
+
+/// Store a salted hash of a lobby's passcode and flag the lobby as having one.
+/// Only private lobbies take a passcode; anywhere else it would never be checked.
+fn set_passcode(ctx: &ReducerContext, lobby_id: &str, passcode: &str) -> Result<(), ErrorCode> {
+    let lobby = ctx.db.lobby().id().find(lobby_id.to_string())
+        .ok_or(ErrorCode::LobbyNotFound)?;
+    if lobby.visibility != LobbyVisibility::Private {
+        return Err(ErrorCode::PasscodeRequiresPrivate);
+    }
+
+    let length = passcode.chars().count();
+    if !(MIN_PASSCODE_LENGTH..=MAX_PASSCODE_LENGTH).contains(&length) {
+        return Err(ErrorCode::InvalidPasscode);
+    }
+
+    let salt = format!("{:016x}", ctx.rng().gen::<u64>());
+    let row = LobbyPasscode {
+        lobby_id: lobby_id.to_string(),
+        passcode_hash: hash_passcode(&salt, passcode),
+        salt,
+    };
+    if ctx.db.lobby_passcode().lobby_id().find(&row.lobby_id).is_some() {
+        ctx.db.lobby_passcode().lobby_id().update(row);
+    } else {
+        ctx.db.lobby_passcode().insert(row);
+    }
+
+    ctx.db.lobby().id().update(Lobby {
+        has_passcode: true,
+        ..lobby
+    });
+    Ok(())
+}
+
+/// Make sure the periodic matcher is running
+fn ensure_matchmaker_scheduled(ctx: &ReducerContext) {
+    if ctx.db.matchmaking_schedule().count() == 0 {
+        ctx.db.matchmaking_schedule().insert(MatchmakingSchedule {
+            scheduled_id: 0,
+            scheduled_at: std::time::Duration::from_secs(MATCHMAKER_INTERVAL_SECS).into(),
+        });
+    }
+}
+
+/// Make sure the periodic audit pruner is running
+fn ensure_audit_pruner_scheduled(ctx: &ReducerContext) {
+    if ctx.db.audit_prune_schedule().count() == 0 {
+        ctx.db.audit_prune_schedule().insert(AuditPruneSchedule {
+            scheduled_id: 0,
+            scheduled_at: std::time::Duration::from_secs(AUDIT_PRUNE_INTERVAL_SECS).into(),
+        });
+    }
+}
+
+/// Run a reducer body, logging the caller and code when it rejects the call
+fn log_rejections(
+    ctx: &ReducerContext,
+    reducer: &str,
+    body: impl FnOnce() -> Result<(), ErrorCode>,
+) -> Result<(), ErrorCode> {
+    let result = body();
+    if let Err(err) = result {
+        log::warn!("{} rejected for {:?}: {}", reducer, ctx.sender, err.code());
+    }
+    result
+}
+
+/// Append an audit event for the reducer call being handled. Only calls
+/// that succeed are recorded: an Err rolls this row back with the rest of
+/// the transaction, so call it once the reducer can no longer fail. The log
+/// line from log_rejections is the only trace a rejected call leaves.
+fn audit(ctx: &ReducerContext, reducer: &str, lobby_id: Option<&str>, payload: serde_json::Value, outcome: &str) {
+    ctx.db.audit_event().insert(AuditEvent {
+        id: 0,
+        timestamp: ctx.timestamp,
+        timestamp_micros: ctx.timestamp.to_micros_since_unix_epoch(),
+        sender: ctx.sender,
+        reducer: reducer.to_string(),
+        lobby_id: lobby_id.map(str::to_string),
+        payload: payload.to_string(),
+        outcome: outcome.to_string(),
+    });
+
+    ensure_audit_pruner_scheduled(ctx);
+}
+
+/// Knock a player out of a live game and record their standing.
+/// `cause` is "orbs_lost" | "timeout" | "resign"; placement is filled in when the game ends.
+fn eliminate_player(
+    ctx: &ReducerContext,
+    player: &LobbyPlayer,
+    move_index: u32,
+    cause: &str,
+    final_orbs: u32,
+) {
+    ctx.db.lobby_player().id().update(LobbyPlayer {
+        is_alive: false,
+        time_bank_ms: 0,
+        eliminated_at_move: Some(move_index),
+        ..player.clone()
+    });
+
+    ctx.db.standing().insert(Standing {
+        id: player.id.clone(),
+        lobby_id: player.lobby_id.clone(),
+        identity: player.identity,
+        address: player.address.clone(),
+        name: player.name.clone(),
+        fid: player.fid,
+        placement: 0,
+        eliminated_at_move: Some(move_index),
+        cause: cause.to_string(),
+        final_orbs,
+        recorded_at: ctx.timestamp,
+    });
+}
+
+/// Record the status a new lobby starts in
+fn record_lobby_created(ctx: &ReducerContext, lobby_id: &str, reason: &str) {
+    ctx.db.lobby_transition().insert(LobbyTransition {
+        id: 0,
+        lobby_id: lobby_id.to_string(),
+        from_status: None,
+        to_status: LobbyStatus::Waiting,
+        reason: reason.to_string(),
+        sender: ctx.sender,
+        at: ctx.timestamp,
+    });
+}
+
+/// Move a lobby to `to` and record the transition. Every status change goes
+/// through here and an illegal move (e.g. finished to live) is refused.
+fn transition_lobby(ctx: &ReducerContext, lobby: Lobby, to: LobbyStatus, reason: &str) -> Result<Lobby, ErrorCode> {
+    if !is_legal_transition(lobby.status, to) {
+        return Err(ErrorCode::IllegalLobbyTransition);
+    }
+
+    ctx.db.lobby_transition().insert(LobbyTransition {
+        id: 0,
+        lobby_id: lobby.id.clone(),
+        from_status: Some(lobby.status),
+        to_status: to,
+        reason: reason.to_string(),
+        sender: ctx.sender,
+        at: ctx.timestamp,
+    });
+
+    Ok(ctx.db.lobby().id().update(Lobby {
+        status: to,
+        updated_at: ctx.timestamp,
+        ..lobby
+    }))
+}
+
+/// Settle a lobby once nothing is left to pay. A lobby with payouts owed
+/// stays finished until the oracle has finished its match on-chain (see
+/// record_match_finished), then settling until the last payout executes.
+fn update_payout_status(ctx: &ReducerContext, lobby_id: &str) -> Result<(), ErrorCode> {
+    let Some(lobby) = ctx.db.lobby().id().find(lobby_id.to_string()) else {
+        return Ok(());
+    };
+    if !matches!(lobby.status, LobbyStatus::Finished | LobbyStatus::Settling) {
+        return Ok(());
+    }
+
+    let settlements: Vec<_> = ctx.db.settlement().lobby_id().filter(lobby_id).collect();
+    if settlements.iter().any(|s| s.status == "pending") {
+        return Ok(());
+    }
+
+    let reason = if settlements.is_empty() { "nothing_to_pay" } else { "payouts_executed" };
+    transition_lobby(ctx, lobby, LobbyStatus::Settled, reason)?;
+    Ok(())
+}
+
+/// Mark a lobby finished with its winner, then update everything that
+/// depends on the final result
+fn finish_lobby(ctx: &ReducerContext, lobby: Lobby, winner: &LobbyPlayer, board: &Board, reason: &str) -> Result<(), ErrorCode> {
+    let lobby = transition_lobby(ctx, Lobby {
+        winner_identity: Some(winner.identity),
+        winner_address: Some(winner.address.clone()),
+        ..lobby
+    }, LobbyStatus::Finished, reason)?;
+
+    ctx.db.standing().insert(Standing {
+        id: winner.id.clone(),
+        lobby_id: winner.lobby_id.clone(),
+        identity: winner.identity,
+        address: winner.address.clone(),
+        name: winner.name.clone(),
+        fid: winner.fid,
+        placement: 1,
+        eliminated_at_move: None,
+        cause: "winner".to_string(),
+        final_orbs: count_orbs(board, &winner.color),
+        recorded_at: ctx.timestamp,
+    });
+
+    record_placements(ctx, &lobby);
+    create_settlements(ctx, &lobby, winner)?;
+    update_ratings(ctx, &lobby);
+    update_leaderboards(ctx, &lobby);
+    finalize_match_stats(ctx, &lobby);
+    award_game_achievements(ctx, &lobby, winner);
+    record_tournament_result(ctx, &lobby)?;
+    update_payout_status(ctx, &lobby.id)
+}
+
+/// Emit the winner's settlement row for a finished paid lobby. The arena
+/// contract pays a single winner, so the whole pool, net of its fee, is theirs.
+fn create_settlements(ctx: &ReducerContext, lobby: &Lobby, winner: &LobbyPlayer) -> Result<(), ErrorCode> {
+    let players = ctx.db.lobby_player().lobby_id().filter(&lobby.id).count();
+    let amount = net_prize_pool(lobby.entry_fee, players as u32)
+        .ok_or(ErrorCode::PrizePoolOverflow)?;
+    if amount == u256::ZERO {
+        return Ok(());
+    }
+
+    ctx.db.settlement().insert(Settlement {
+        id: 0,
+        lobby_id: lobby.id.clone(),
+        chain_id: lobby.chain_id,
+        match_id: lobby.match_id,
+        arena_address: lobby.arena_address.clone(),
+        placement: 1,
+        identity: winner.identity,
+        address: winner.address.clone(),
+        amount,
+        status: "pending".to_string(),
+        tx_hash: None,
+        created_at: ctx.timestamp,
+        executed_at: None,
+    });
+    Ok(())
+}
+
+fn is_oracle(ctx: &ReducerContext, identity: Identity) -> bool {
+    ctx.db.oracle().identity().find(identity).is_some()
+}
+
+/// Fill in final placements on the standing rows of a finished lobby
+fn record_placements(ctx: &ReducerContext, lobby: &Lobby) {
+    let players: Vec<_> = ctx.db.lobby_player().lobby_id().filter(&lobby.id).collect();
+
+    for (player, placement) in final_placements(lobby.winner_identity, players) {
+        if let Some(standing) = ctx.db.standing().id().find(&player.id) {
+            ctx.db.standing().id().update(Standing {
+                placement,
+                ..standing
+            });
+        }
+    }
+}
+
+/// Rank the players of a finished lobby: the winner first, then by how late
+/// they were eliminated. Players knocked out on the same move share a place.
+fn final_placements(winner: Option<Identity>, mut players: Vec<LobbyPlayer>) -> Vec<(LobbyPlayer, u32)> {
+    let sort_key = |p: &LobbyPlayer| {
+        let is_winner = Some(p.identity) == winner;
+        (!is_winner, std::cmp::Reverse(p.eliminated_at_move.unwrap_or(u32::MAX)))
+    };
+    players.sort_by_key(sort_key);
+
+    let keys: Vec<_> = players.iter().map(sort_key).collect();
+    players
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let placement = 1 + keys.iter().filter(|k| **k < keys[i]).count() as u32;
+            (p, placement)
+        })
+        .collect()
+}
+
+/// Current rating for a player in a pool (DEFAULT_RATING if they have none)
+fn current_rating(ctx: &ReducerContext, player_key: &str, max_players: u32, variant: &str) -> i32 {
+    ctx.db.player_rating().id().find(rating_id(player_key, max_players, variant))
+        .map(|r| r.rating)
+        .unwrap_or(DEFAULT_RATING)
+}
+
+/// Multi-player Elo: every pair of players is scored as a head-to-head
+/// result by finishing position, and each player's change is averaged over
+/// their opponents.
+fn update_ratings(ctx: &ReducerContext, lobby: &Lobby) {
+    let players: Vec<_> = ctx.db.lobby_player().lobby_id().filter(&lobby.id).collect();
+    if players.len() < 2 {
+        return;
+    }
+
+    let variant = variant_name(lobby.time_control);
+    let placements = final_placements(lobby.winner_identity, players);
+
+    let existing: Vec<_> = placements.iter()
+        .map(|(p, _)| {
+            let key = player_key(p.identity, &p.address);
+            ctx.db.player_rating().id().find(rating_id(&key, lobby.max_players, variant))
+        })
+        .collect();
+    let ratings: Vec<f64> = existing.iter()
+        .map(|r| r.as_ref().map_or(DEFAULT_RATING, |r| r.rating) as f64)
+        .collect();
+
+    let opponents = (placements.len() - 1) as f64;
+    for (i, (player, placement)) in placements.iter().enumerate() {
+        let mut score_diff = 0.0;
+        for (j, (_, other_placement)) in placements.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let expected = 1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+            let actual = match placement.cmp(other_placement) {
+                std::cmp::Ordering::Less => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Greater => 0.0,
+            };
+            score_diff += actual - expected;
+        }
+
+        let games_played = existing[i].as_ref().map_or(0, |r| r.games_played);
+        let k = if games_played < PROVISIONAL_GAMES { RATING_K_PROVISIONAL } else { RATING_K_ESTABLISHED };
+        let new_rating = (ratings[i] + k * score_diff / opponents).round() as i32;
+        let won = *placement == 1;
+
+        let key = player_key(player.identity, &player.address);
+        let row = PlayerRating {
+            id: rating_id(&key, lobby.max_players, variant),
+            player_key: key,
+            identity: player.identity,
+            max_players: lobby.max_players,
+            variant: variant.to_string(),
+            rating: new_rating,
+            games_played: games_played + 1,
+            wins: existing[i].as_ref().map_or(0, |r| r.wins) + won as u32,
+            provisional: games_played + 1 < PROVISIONAL_GAMES,
+            updated_at: ctx.timestamp,
+        };
+        if existing[i].is_some() {
+            ctx.db.player_rating().id().update(row);
+        } else {
+            ctx.db.player_rating().insert(row);
+        }
+    }
+}
+
+/// Get the caller's profile; lobbies and the queue take player details from it.
+/// Its wallet must still be linked to the caller.
+fn require_profile(ctx: &ReducerContext) -> Result<Profile, ErrorCode> {
+    let profile = ctx.db.profile().identity().find(ctx.sender)
+        .ok_or(ErrorCode::ProfileNotFound)?;
+
+    if !is_wallet_linked(ctx, &profile.primary_address, ctx.sender) {
+        return Err(ErrorCode::WalletNotLinked);
+    }
+
+    Ok(profile)
+}
+
+/// Farcaster FID linked to an identity, if any
+fn linked_fid(ctx: &ReducerContext, identity: Identity) -> Option<u64> {
+    ctx.db.farcaster_link().identity().find(identity).map(|link| link.fid)
+}
+
+/// Store a verified Farcaster link, replacing any previous link for the
+/// identity and moving the FID over if another identity held it. Only call
+/// this once the claim is proven (custody signature or oracle attestation).
+fn store_farcaster_link(ctx: &ReducerContext, link: FarcasterLink) -> Result<(), ErrorCode> {
+    if link.username.is_empty() || link.username.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+        return Err(ErrorCode::InvalidFarcasterUsername);
+    }
+    if link.pfp_url.as_ref().is_some_and(|url| url.len() > MAX_AVATAR_URL_LENGTH) {
+        return Err(ErrorCode::UrlTooLong);
+    }
+
+    ctx.db.farcaster_link().fid().delete(link.fid);
+    ctx.db.farcaster_link().identity().delete(link.identity);
+    ctx.db.farcaster_link().insert(link);
+    Ok(())
+}
+
+/// Whether `address` has been proven (via link_wallet) to belong to `identity`
+fn is_wallet_linked(ctx: &ReducerContext, address: &str, identity: Identity) -> bool {
+    ctx.db.wallet_link().address().find(address.to_lowercase())
+        .is_some_and(|link| link.identity == identity)
+}
+
+/// Id of the active season, starting the first one if none exists yet
+fn current_season_id(ctx: &ReducerContext) -> u32 {
+    if let Some(season) = ctx.db.season().iter().find(|s| s.is_active) {
+        return season.id;
+    }
+
+    ctx.db.season().insert(Season {
+        id: 0,
+        name: "Season 1".to_string(),
+        is_active: true,
+        started_at: ctx.timestamp,
+        ended_at: None,
+    }).id
+}
+
+/// Add a finished lobby's results to the active season's leaderboards
+fn update_leaderboards(ctx: &ReducerContext, lobby: &Lobby) {
+    let season_id = current_season_id(ctx);
+    let variant = variant_name(lobby.time_control);
+
+    for player in ctx.db.lobby_player().lobby_id().filter(&lobby.id) {
+        let key = player_key(player.identity, &player.address);
+        let id = format!("{}_{}_{}_{}_{}", season_id, lobby.chain_id, lobby.max_players, variant, key);
+
+        let won = Some(player.identity) == lobby.winner_identity;
+        let earned = ctx.db.settlement()
+            .lobby_id()
+            .filter(&lobby.id)
+            .filter(|s| s.identity == player.identity)
+            .fold(u256::ZERO, |total, s| total + s.amount);
+        let rating = current_rating(ctx, &key, lobby.max_players, variant);
+
+        match ctx.db.leaderboard_entry().id().find(&id) {
+            Some(entry) => {
+                ctx.db.leaderboard_entry().id().update(LeaderboardEntry {
+                    name: player.name,
+                    games: entry.games + 1,
+                    wins: entry.wins + won as u32,
+                    earnings: entry.earnings + earned,
+                    rating,
+                    updated_at: ctx.timestamp,
+                    ..entry
+                });
+            }
+            None => {
+                ctx.db.leaderboard_entry().insert(LeaderboardEntry {
+                    id,
+                    season_id,
+                    chain_id: lobby.chain_id,
+                    max_players: lobby.max_players,
+                    variant: variant.to_string(),
+                    player_key: key,
+                    identity: player.identity,
+                    name: player.name,
+                    games: 1,
+                    wins: won as u32,
+                    earnings: earned,
+                    rating,
+                    updated_at: ctx.timestamp,
+                });
+            }
+        }
+    }
+}
+
+/// Competition ranks (1, 2, 2, 4, ...) for `values`, highest first
+fn rank_descending<T: PartialOrd>(values: &[T]) -> Vec<u32> {
+    values.iter()
+        .map(|v| 1 + values.iter().filter(|other| *other > v).count() as u32)
+        .collect()
+}
+
+/// Open the next round of a tournament: a locked lobby for each group of two
+/// or more players, and a bye for anyone drawn alone
+fn open_tournament_round(ctx: &ReducerContext, tournament: Tournament, groups: Vec<Vec<TournamentEntry>>) -> Result<(), ErrorCode> {
+    let round = tournament.current_round + 1;
+
+    for (slot, group) in groups.iter().enumerate() {
+        // An empty bracket slot is a match nobody reached
+        let (status, winner_identity) = match group.as_slice() {
+            [] => ("forfeit", None),
+            [entry] => ("bye", Some(entry.identity)),
+            _ => ("live", None),
+        };
+        let lobby_id = match status {
+            "live" => Some(open_tournament_lobby(ctx, &tournament, group)?),
+            _ => None,
+        };
+
+        ctx.db.tournament_match().insert(TournamentMatch {
+            id: format!("{}_{}_{}", tournament.id, round, slot),
+            tournament_id: tournament.id.clone(),
+            round,
+            slot: slot as u32,
+            lobby_id,
+            players: group.iter().map(|e| e.identity).collect(),
+            winner_identity,
+            status: status.to_string(),
+        });
+    }
+
+    let deadline = ctx.timestamp
+        + std::time::Duration::from_secs(tournament.round_deadline_minutes as u64 * 60);
+    let tournament = ctx.db.tournament().id().update(Tournament {
+        current_round: round,
+        round_deadline: Some(deadline),
+        updated_at: ctx.timestamp,
+        ..tournament
+    });
+
+    log::info!("Tournament {} round {} opened with {} matches", tournament.id, round, groups.len());
+
+    // Nothing to play: a round of byes decides itself
+    if groups.iter().all(|group| group.len() < 2) {
+        advance_tournament(ctx, &tournament.id)?;
+    }
+    Ok(())
+}
+
+/// Create the lobby for one tournament match. The best seed hosts and
+/// creates the on-chain match; seed order decides turn order.
+fn open_tournament_lobby(ctx: &ReducerContext, tournament: &Tournament, group: &[TournamentEntry]) -> Result<String, ErrorCode> {
+    let now = ctx.timestamp;
+
+    let lobby = open_lobby(ctx, LobbySettings {
+        chain_id: tournament.chain_id,
+        match_id: 0, // Set by the host once the on-chain match exists
+        arena_address: tournament.arena_address.clone(),
+        entry_fee: tournament.entry_fee,
+        max_players: group.len() as u32,
+        time_control: tournament.time_control,
+        is_locked: true, // Seats belong to the drawn players
+        visibility: LobbyVisibility::Unlisted,
+        allow_spectators: true,
+        max_spectators: None,
+        previous_lobby_id: None,
+        tournament_id: Some(tournament.id.clone()),
+    }, group.iter().enumerate().map(|(i, entry)| Seat {
+        identity: entry.identity,
+        address: entry.address.clone(),
+        name: entry.name.clone(),
+        color: None,
+        has_deposited: tournament.entry_fee == u256::ZERO, // Free cups have nothing to deposit
+        joined_at: now + std::time::Duration::from_micros(i as u64),
+    }).collect(), "tournament")?;
+
+    Ok(lobby.id)
+}
+
+/// Close a tournament match with its winner (None if nobody showed up);
+/// in a knockout everyone else in it is out
+fn settle_tournament_match(ctx: &ReducerContext, tournament_match: TournamentMatch, winner: Option<Identity>, status: &str) {
+    let knockout = ctx.db.tournament().id().find(&tournament_match.tournament_id)
+        .is_some_and(|t| t.format == TournamentFormat::Knockout);
+    let losers = tournament_match.players.iter().filter(|i| knockout && Some(**i) != winner);
+
+    for identity in losers {
+        let entry_id = format!("{}_{:?}", tournament_match.tournament_id, identity);
+        if let Some(entry) = ctx.db.tournament_entry().id().find(&entry_id) {
+            ctx.db.tournament_entry().id().update(TournamentEntry {
+                status: "eliminated".to_string(),
+                eliminated_in_round: Some(tournament_match.round),
+                ..entry
+            });
+        }
+    }
+
+    ctx.db.tournament_match().id().update(TournamentMatch {
+        winner_identity: winner,
+        status: status.to_string(),
+        ..tournament_match
+    });
+}
+
+/// Advance the winner of a finished tournament lobby
+fn record_tournament_result(ctx: &ReducerContext, lobby: &Lobby) -> Result<(), ErrorCode> {
+    let Some(tournament_id) = &lobby.tournament_id else {
+        return Ok(());
+    };
+    let tournament_match = ctx.db.tournament_match()
+        .tournament_id()
+        .filter(tournament_id)
+        .find(|m| m.lobby_id.as_ref() == Some(&lobby.id));
+
+    if let Some(tournament_match) = tournament_match {
+        settle_tournament_match(ctx, tournament_match, lobby.winner_identity, "finished");
+        advance_tournament(ctx, tournament_id)?;
+    }
+    Ok(())
+}
+
+/// Once every match of the current round is decided, draw the next round
+/// or crown the champion
+fn advance_tournament(ctx: &ReducerContext, tournament_id: &str) -> Result<(), ErrorCode> {
+    let Some(tournament) = ctx.db.tournament().id().find(tournament_id.to_string()) else {
+        return Ok(());
+    };
+    if tournament.status != "running" {
+        return Ok(());
+    }
+
+    let mut matches: Vec<_> = ctx.db.tournament_match()
+        .tournament_id()
+        .filter(tournament_id)
+        .filter(|m| m.round == tournament.current_round)
+        .collect();
+    if matches.iter().any(|m| m.status == "live") {
+        return Ok(());
+    }
+    matches.sort_by_key(|m| m.slot);
+
+    if let TournamentFormat::Swiss(settings) = tournament.format {
+        let standings = update_swiss_standings(ctx, tournament_id, settings.tiebreak);
+        if tournament.current_round >= settings.rounds {
+            finish_tournament(ctx, tournament, standings.into_iter().next());
+        } else {
+            let groups = swiss_pairings(ctx, tournament_id, standings);
+            open_tournament_round(ctx, tournament, groups)?;
+        }
+        return Ok(());
+    }
+
+    // One entry per slot, so bracket neighbours stay aligned
+    let winners: Vec<Option<TournamentEntry>> = matches.iter()
+        .map(|m| {
+            let identity = m.winner_identity?;
+            ctx.db.tournament_entry().id().find(format!("{}_{:?}", tournament_id, identity))
+        })
+        .collect();
+
+    let advancing = winners.iter().flatten().count();
+    if advancing <= 1 {
+        finish_tournament(ctx, tournament, winners.into_iter().flatten().next());
+        return Ok(());
+    }
+
+    let groups = if tournament.players_per_match == 2 {
+        // Bracket: winners of neighbouring slots meet
+        next_bracket_round(winners)
+            .into_iter()
+            .map(|mut pair| {
+                pair.sort_by_key(|e| e.seed);
+                pair
+            })
+            .collect()
+    } else {
+        // Heats: reshuffle the survivors by seed
+        let mut seeded: Vec<_> = winners.into_iter().flatten().collect();
+        seeded.sort_by_key(|e| e.seed);
+        snake_heats(&seeded, tournament.players_per_match as usize)
+    };
+    open_tournament_round(ctx, tournament, groups)
+}
+
+/// Close a tournament with its champion (None if nobody is left)
+fn finish_tournament(ctx: &ReducerContext, tournament: Tournament, winner: Option<TournamentEntry>) {
+    let winner_identity = winner.as_ref().map(|e| e.identity);
+    if let Some(entry) = winner {
+        ctx.db.tournament_entry().id().update(TournamentEntry {
+            status: "winner".to_string(),
+            ..entry
+        });
+    }
+
+    let tournament = ctx.db.tournament().id().update(Tournament {
+        status: "finished".to_string(),
+        round_deadline: None,
+        winner_identity,
+        updated_at: ctx.timestamp,
+        ..tournament
+    });
+
+    log::info!("Tournament {} finished", tournament.id);
+}
+
+/// Recompute Swiss scores and tiebreaks from every decided match and rank
+/// the entries. Returns the entries in standing order.
+fn update_swiss_standings(ctx: &ReducerContext, tournament_id: &str, tiebreak: Tiebreak) -> Vec<TournamentEntry> {
+    let matches: Vec<_> = ctx.db.tournament_match()
+        .tournament_id()
+        .filter(tournament_id)
+        .filter(|m| m.status != "live")
+        .collect();
+    let entries: Vec<_> = ctx.db.tournament_entry().tournament_id().filter(tournament_id).collect();
+
+    swiss_standings(entries, &matches, tiebreak)
+        .into_iter()
+        .map(|entry| ctx.db.tournament_entry().id().update(entry))
+        .collect()
+}
+
+/// Score and rank `entries` from the decided `matches`: wins (byes count),
+/// then the chosen tiebreak, then the other one, then seed.
+fn swiss_standings(entries: Vec<TournamentEntry>, matches: &[TournamentMatch], tiebreak: Tiebreak) -> Vec<TournamentEntry> {
+    let mut scores: std::collections::HashMap<Identity, u32> = std::collections::HashMap::new();
+    for winner in matches.iter().filter_map(|m| m.winner_identity) {
+        *scores.entry(winner).or_default() += 1;
+    }
+    let score_of = |identity: &Identity| scores.get(identity).copied().unwrap_or(0);
+
+    let mut entries: Vec<_> = entries.into_iter()
+        .map(|entry| {
+            let mut buchholz = 0;
+            let mut sonneborn_berger = 0;
+            for m in matches.iter().filter(|m| m.players.contains(&entry.identity)) {
+                for opponent in m.players.iter().filter(|p| **p != entry.identity) {
+                    buchholz += score_of(opponent);
+                    if m.winner_identity == Some(entry.identity) {
+                        sonneborn_berger += score_of(opponent);
+                    }
+                }
+            }
+            TournamentEntry {
+                score: score_of(&entry.identity),
+                buchholz,
+                sonneborn_berger,
+                ..entry
+            }
+        })
+        .collect();
+
+    entries.sort_by_key(|e| {
+        let (first, second) = match tiebreak {
+            Tiebreak::Buchholz => (e.buchholz, e.sonneborn_berger),
+            Tiebreak::SonnebornBerger => (e.sonneborn_berger, e.buchholz),
+        };
+        (std::cmp::Reverse((e.score, first, second)), e.seed)
+    });
+
+    entries.into_iter()
+        .enumerate()
+        .map(|(i, entry)| TournamentEntry {
+            rank: i as u32 + 1,
+            ..entry
+        })
+        .collect()
+}
+
+/// Draw the next Swiss round from `standings` (best first): pair within
+/// score groups, dropping down only to avoid rematches. With an odd field the
+/// lowest-ranked player who has not had a bye sits out with one.
+fn swiss_pairings(ctx: &ReducerContext, tournament_id: &str, standings: Vec<TournamentEntry>) -> Vec<Vec<TournamentEntry>> {
+    let previous: Vec<_> = ctx.db.tournament_match().tournament_id().filter(tournament_id).collect();
+    swiss_round(standings, &previous)
+}
+
+/// Pair `standings` (best first) given every earlier match of the tournament.
+/// The bye, if any, goes last so the board slots follow the standings.
+fn swiss_round(mut standings: Vec<TournamentEntry>, previous: &[TournamentMatch]) -> Vec<Vec<TournamentEntry>> {
+    let mut played = std::collections::HashSet::new();
+    for m in previous.iter().filter(|m| m.players.len() == 2) {
+        played.insert((m.players[0], m.players[1]));
+        played.insert((m.players[1], m.players[0]));
+    }
+
+    let bye = swiss_bye(&standings, previous).map(|i| standings.remove(i));
+
+    let order: Vec<_> = standings.iter().map(|e| e.identity).collect();
+    let mut budget = SWISS_PAIRING_BUDGET;
+    let pairs = pair_without_rematches(&order, &played, &mut budget)
+        // Too few opponents left to avoid every rematch: pair straight down
+        .unwrap_or_else(|| order.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+
+    let entry = |identity: Identity| standings.iter()
+        .find(|e| e.identity == identity)
+        .cloned()
+        .expect("Pairings are drawn from the standings");
+    let mut pairings: Vec<Vec<TournamentEntry>> = pairs.into_iter()
+        .map(|(a, b)| vec![entry(a), entry(b)])
+        .collect();
+
+    pairings.extend(bye.map(|e| vec![e]));
+    pairings
+}
+
+/// Index in `standings` of who sits out with the bye: None for an even
+/// field, else the lowest-ranked player without a bye yet (or the last
+/// player once everyone has had one)
+fn swiss_bye(standings: &[TournamentEntry], previous: &[TournamentMatch]) -> Option<usize> {
+    if standings.len().is_multiple_of(2) {
+        return None;
+    }
+    let had_bye = |e: &TournamentEntry| previous.iter()
+        .any(|m| m.status == "bye" && m.players.contains(&e.identity));
+    standings.iter().rposition(|e| !had_bye(e))
+        .or(Some(standings.len() - 1))
+}
+
+/// Add one move to the mover's match statistics
+fn record_move_stats(ctx: &ReducerContext, mover: &LobbyPlayer, reaction: &ChainReaction, orbs: u32, think_ms: u64) {
+    let Some(stats) = ctx.db.match_stats().id().find(&mover.id) else {
+        return;
+    };
+
+    ctx.db.match_stats().id().update(MatchStats {
+        moves_made: stats.moves_made + 1,
+        explosions: stats.explosions + reaction.explosions,
+        longest_chain: stats.longest_chain.max(reaction.explosions),
+        cells_captured: stats.cells_captured + reaction.cells_captured,
+        peak_orbs: stats.peak_orbs.max(orbs),
+        total_think_ms: stats.total_think_ms + think_ms,
+        updated_at: ctx.timestamp,
+        ..stats
+    });
+}
+
+/// Work out the averages and freeze the match statistics of a finished lobby
+fn finalize_match_stats(ctx: &ReducerContext, lobby: &Lobby) {
+    let rows: Vec<_> = ctx.db.match_stats().lobby_id().filter(&lobby.id).collect();
+
+    for stats in rows {
+        ctx.db.match_stats().id().update(MatchStats {
+            avg_think_ms: stats.total_think_ms.checked_div(stats.moves_made as u64).unwrap_or(0),
+            is_final: true,
+            updated_at: ctx.timestamp,
+            ..stats
+        });
+    }
+}
+
+/// Insert any achievement definitions that are missing (safe to repeat)
+fn seed_achievements(ctx: &ReducerContext) {
+    for (id, name, description, target) in ACHIEVEMENTS {
+        if ctx.db.achievement().id().find(id.to_string()).is_none() {
+            ctx.db.achievement().insert(Achievement {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                target,
+            });
+        }
+    }
+}
+
+/// A player's current progress toward an achievement
+fn achievement_progress(ctx: &ReducerContext, identity: Identity, achievement_id: &str) -> u32 {
+    ctx.db.player_achievement().id().find(format!("{:?}_{}", identity, achievement_id))
+        .map_or(0, |a| a.progress)
+}
+
+/// Raise a player's progress on an achievement to `progress`, unlocking it
+/// once the target is reached. Progress never goes down, and an unlocked
+/// achievement is left alone, so repeated awards are harmless.
+fn record_achievement_progress(ctx: &ReducerContext, identity: Identity, achievement_id: &str, progress: u32) {
+    // Catalogues added by an upgrade appear once seed_catalogues has run
+    let Some(achievement) = ctx.db.achievement().id().find(achievement_id.to_string()) else {
+        log::warn!("Achievement {} is not seeded yet, run seed_catalogues", achievement_id);
+        return;
+    };
+
+    let id = format!("{:?}_{}", identity, achievement_id);
+    let existing = ctx.db.player_achievement().id().find(&id);
+    let previous = existing.as_ref().map_or(0, |a| a.progress);
+    if progress <= previous || existing.as_ref().is_some_and(|a| a.unlocked_at.is_some()) {
+        return;
+    }
+
+    let progress = progress.min(achievement.target);
+    let unlocked_at = (progress >= achievement.target).then_some(ctx.timestamp);
+    let row = PlayerAchievement {
+        id,
+        identity,
+        achievement_id: achievement.id,
+        progress,
+        unlocked_at,
+        updated_at: ctx.timestamp,
+    };
+    if existing.is_some() {
+        ctx.db.player_achievement().id().update(row);
+    } else {
+        ctx.db.player_achievement().insert(row);
+    }
+
+    if unlocked_at.is_some() {
+        log::info!("Player {:?} unlocked {}", identity, achievement_id);
+    }
+}
+
+/// After a move, update every player's achievement tracker from the board
+/// change and award what a single move can earn
+fn track_move_achievements(
+    ctx: &ReducerContext,
+    players: &[LobbyPlayer],
+    mover: &LobbyPlayer,
+    before: &Board,
+    after: &Board,
+    explosions: u32,
+) {
+    record_achievement_progress(ctx, mover.identity, "chain_reaction", explosions);
+
+    for player in players {
+        let cells = count_cells(after, &player.color);
+        let lost_cell = before.iter().flatten()
+            .zip(after.iter().flatten())
+            .any(|(old, new)| {
+                old.owner.as_deref() == Some(player.color.as_str())
+                    && new.owner.as_ref().is_some_and(|owner| *owner != player.color)
+            });
+
+        let tracker = ctx.db.achievement_tracker().id().find(&player.id);
+        let previous_peak = tracker.as_ref().map_or(0, |t| t.peak_cells);
+        let row = AchievementTracker {
+            id: player.id.clone(),
+            lobby_id: player.lobby_id.clone(),
+            peak_cells: previous_peak.max(cells),
+            lost_cell: lost_cell || tracker.as_ref().is_some_and(|t| t.lost_cell),
+            fell_to_one_cell: (cells == 1 && previous_peak > 1)
+                || tracker.as_ref().is_some_and(|t| t.fell_to_one_cell),
+        };
+        if tracker.is_some() {
+            ctx.db.achievement_tracker().id().update(row);
+        } else {
+            ctx.db.achievement_tracker().insert(row);
+        }
+    }
+}
+
+/// Award the winner's end-of-game achievements
+fn award_game_achievements(ctx: &ReducerContext, lobby: &Lobby, winner: &LobbyPlayer) {
+    if let Some(tracker) = ctx.db.achievement_tracker().id().find(&winner.id) {
+        if tracker.peak_cells > 0 && !tracker.lost_cell {
+            record_achievement_progress(ctx, winner.identity, "flawless", 1);
+        }
+        if tracker.fell_to_one_cell {
+            record_achievement_progress(ctx, winner.identity, "comeback", 1);
+        }
+    }
+
+    if ctx.db.lobby_player().lobby_id().filter(&lobby.id).count() == 5 {
+        let wins = achievement_progress(ctx, winner.identity, "five_way_victor");
+        record_achievement_progress(ctx, winner.identity, "five_way_victor", wins + 1);
+    }
+}
+
+/// Create the empty game state for a new lobby, sized for its player count
+fn insert_empty_game_state(ctx: &ReducerContext, lobby_id: &str, max_players: u32) {
+    // Calculate board size based on max players
+    let (rows, cols) = get_board_size(max_players);
+    let board = create_empty_board(rows, cols);
+
+    // Create empty game state with board dimensions
+    ctx.db.game_state().insert(GameState {
+        lobby_id: lobby_id.to_string(),
+        board_json: serde_json::to_string(&board).unwrap(),
+        rows: rows as u32,
+        cols: cols as u32,
+        current_player_index: 0,
+        move_count: 0,
+        last_move_at: ctx.timestamp,
+        turn_deadline: ctx.timestamp, // Will be set correctly on start_game
+        turn_lock_until: None,
+        last_move_player: None,
+    });
+}
+
+/// Settings of a lobby being opened; everything else starts fresh
+struct LobbySettings {
+    chain_id: u32,
+    match_id: u64,               // 0 until the host creates the on-chain match
+    arena_address: String,
+    entry_fee: u256,
+    max_players: u32,
+    time_control: TimeControl,
+    is_locked: bool,
+    visibility: LobbyVisibility,
+    allow_spectators: bool,
+    max_spectators: Option<u32>,
+    previous_lobby_id: Option<String>,
+    tournament_id: Option<String>,
+}
+
+/// A player seated when a lobby opens
+struct Seat {
+    identity: Identity,
+    address: String,
+    name: String,
+    color: Option<String>,       // None picks one with assign_color
+    has_deposited: bool,
+    joined_at: Timestamp,        // Decides turn order
+}
+
+/// Open a waiting lobby with its first seats (the first seat hosts), its
+/// empty game state and its creation transition
+fn open_lobby(ctx: &ReducerContext, settings: LobbySettings, seats: Vec<Seat>, reason: &str) -> Result<Lobby, ErrorCode> {
+    let now = ctx.timestamp;
+    let host = &seats[0];
+
+    let lobby = ctx.db.lobby().insert(Lobby {
+        id: generate_id(ctx),
+        room_code: generate_room_code(ctx)?,
+        chain_id: settings.chain_id,
+        match_id: settings.match_id,
+        arena_address: settings.arena_address,
+        host_identity: host.identity,
+        host_address: host.address.clone(),
+        entry_fee: settings.entry_fee,
+        max_players: settings.max_players,
+        status: LobbyStatus::Waiting,
+        time_control: settings.time_control,
+        is_locked: settings.is_locked,
+        visibility: settings.visibility,
+        has_passcode: false,
+        allow_spectators: settings.allow_spectators,
+        max_spectators: settings.max_spectators,
+        spectator_count: 0,
+        previous_lobby_id: settings.previous_lobby_id,
+        rematch_lobby_id: None,
+        tournament_id: settings.tournament_id,
+        winner_identity: None,
+        winner_address: None,
+        created_at: now,
+        updated_at: now,
+    });
+    record_lobby_created(ctx, &lobby.id, reason);
+
+    let mut used_colors = std::collections::HashSet::new();
+    for (i, seat) in seats.into_iter().enumerate() {
+        let color = match seat.color {
+            Some(color) if !used_colors.contains(&color) => color,
+            _ => assign_color(ctx, seat.identity, &used_colors)
+                .expect("Matches have fewer players than colours"),
+        };
+        used_colors.insert(color.clone());
+
+        ctx.db.lobby_player().insert(LobbyPlayer {
+            id: format!("{}_{:?}", lobby.id, seat.identity),
+            lobby_id: lobby.id.clone(),
+            identity: seat.identity,
+            address: seat.address,
+            name: seat.name,
+            color,
+            is_host: i == 0,
+            is_alive: true,
+            has_deposited: seat.has_deposited,
+            is_ready: false,
+            time_bank_ms: initial_time_bank_ms(lobby.time_control),
+            eliminated_at_move: None,
+            fid: linked_fid(ctx, seat.identity),
+            joined_at: seat.joined_at,
+        });
+    }
+
+    insert_empty_game_state(ctx, &lobby.id, lobby.max_players);
+
+    Ok(lobby)
+}
+
+/// Queue a refund of the entry fee for a player who paid into a lobby they
+/// no longer play in. `reason` is "kicked" | "left" | "round_timeout" | "deposit_timeout".
+fn refund_deposit(ctx: &ReducerContext, lobby: &Lobby, player: &LobbyPlayer, reason: &str) {
+    if !player.has_deposited || lobby.entry_fee == u256::ZERO {
+        return;
+    }
+
+    ctx.db.refund().insert(Refund {
+        id: 0,
+        lobby_id: lobby.id.clone(),
+        chain_id: lobby.chain_id,
+        match_id: lobby.match_id,
+        identity: player.identity,
+        address: player.address.clone(),
+        amount: lobby.entry_fee,
+        reason: reason.to_string(),
+        processed: false,
+        tx_hash: None,
+        created_at: ctx.timestamp,
+        processed_at: None,
+    });
+}
+
+/// Make `new_host` the host of the lobby, clearing the flag on the previous host.
+/// Only the lobby's host role moves: the on-chain match keeps the creator who
+/// opened it, so `host_address` can differ from the arena's match creator.
+fn set_host(ctx: &ReducerContext, lobby: Lobby, new_host: LobbyPlayer) -> Lobby {
+    let previous_host_id = format!("{}_{:?}", lobby.id, lobby.host_identity);
+    if let Some(previous_host) = ctx.db.lobby_player().id().find(&previous_host_id) {
+        ctx.db.lobby_player().id().update(LobbyPlayer {
+            is_host: false,
+            ..previous_host
+        });
+    }
+
+    let host_identity = new_host.identity;
+    let host_address = new_host.address.clone();
+    ctx.db.lobby_player().id().update(LobbyPlayer {
+        is_host: true,
+        ..new_host
+    });
+
+    ctx.db.lobby().id().update(Lobby {
+        host_identity,
+        host_address,
+        updated_at: ctx.timestamp,
+        ..lobby
+    })
+}
+
+/// Pass the host role away from `outgoing` to the best remaining player:
+/// alive players first, then deposited ones, then whoever has waited longest.
+/// Returns None when nobody else is left in the lobby.
+fn migrate_host(ctx: &ReducerContext, lobby: Lobby, outgoing: Identity) -> Option<Lobby> {
+    let successor = ctx.db.lobby_player()
+        .lobby_id()
+        .filter(&lobby.id)
+        .filter(|p| p.identity != outgoing)
+        .min_by_key(|p| (!p.is_alive, !p.has_deposited, p.joined_at))?;
+
+    Some(set_host(ctx, lobby, successor))
+}
+
+/// Move the host role off a host who has been eliminated from a live game
+fn migrate_host_if_eliminated(ctx: &ReducerContext, lobby: Lobby) {
+    let host_id = format!("{}_{:?}", lobby.id, lobby.host_identity);
+    let host_alive = ctx.db.lobby_player().id().find(&host_id)
+        .is_some_and(|p| p.is_alive);
+
+    if !host_alive {
+        let outgoing = lobby.host_identity;
+        if let Some(lobby) = migrate_host(ctx, lobby, outgoing) {
+            log::info!("Host of lobby {} eliminated, new host {:?}", lobby.id, lobby.host_identity);
+        }
+    }
+}
+
+/// Remove a spectator row and keep the lobby's spectator count in sync
+fn remove_spectator(ctx: &ReducerContext, spectator: Spectator) {
+    ctx.db.spectator().id().delete(&spectator.id);
+
+    if let Some(lobby) = ctx.db.lobby().id().find(&spectator.lobby_id) {
+        ctx.db.lobby().id().update(Lobby {
+            spectator_count: lobby.spectator_count.saturating_sub(1),
+            ..lobby
+        });
+    }
+}
+
+fn is_moderator(ctx: &ReducerContext, identity: Identity) -> bool {
+    ctx.db.moderator().identity().find(identity).is_some()
+}
+
+// ============================================================================
+// LIFECYCLE REDUCERS
+// ============================================================================
+
+#[spacetimedb::reducer(init)]
+pub fn init(ctx: &ReducerContext) {
+    // The publisher is the first moderator
+    ctx.db.moderator().insert(Moderator {
+        identity: ctx.sender,
+        added_by: ctx.sender,
+        added_at: ctx.timestamp,
+    });
+
+    ensure_matchmaker_scheduled(ctx);
+    ensure_audit_pruner_scheduled(ctx);
+    current_season_id(ctx);
+    seed_achievements(ctx);
+    seed_error_codes(ctx);
+
+    log::info!("Chain Reaction module initialized!");
+}
+
+/// Bring the achievement and error-code catalogues up to date after an
+/// upgrade, since init only runs on first publish (moderators only)
+#[spacetimedb::reducer]
+pub fn seed_catalogues(ctx: &ReducerContext) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "seed_catalogues", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+
+        seed_achievements(ctx);
+        seed_error_codes(ctx);
+
+        audit(ctx, "seed_catalogues", None, serde_json::json!({}), "ok");
+        log::info!("Catalogues seeded by {:?}", ctx.sender);
+
+        Ok(())
+    })
+}
+
+#[spacetimedb::reducer(client_connected)]
+pub fn identity_connected(ctx: &ReducerContext) {
+    log::info!("Client connected: {:?}", ctx.sender);
+}
+
+#[spacetimedb::reducer(client_disconnected)]
+pub fn identity_disconnected(ctx: &ReducerContext) {
+    log::info!("Client disconnected: {:?}", ctx.sender);
+
+    // Stop spectating everything this client was watching
+    let spectating: Vec<_> = ctx.db.spectator()
+        .identity()
+        .filter(&ctx.sender)
+        .collect();
+
+    for spectator in spectating {
+        remove_spectator(ctx, spectator);
+    }
+}
+
+// ============================================================================
+// GAME REDUCERS
+// ============================================================================
+
+/// Create a new lobby hosted by the caller. The id and room code it gets are
+/// published to the caller's last_created_lobby row.
+#[spacetimedb::reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn create_lobby(
+    ctx: &ReducerContext,
+    chain_id: u32,
+    match_id: u64,
+    arena_address: String,
+    entry_fee: String,
+    max_players: u32,
+    time_control: TimeControl,
+    visibility: LobbyVisibility,
+    passcode: Option<String>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "create_lobby", move || {
+        if !(2..=5).contains(&max_players) {
+            return Err(ErrorCode::InvalidPlayerCount);
+        }
+        validate_time_control(time_control)?;
+        let entry_fee = parse_entry_fee(&entry_fee)?;
+        let profile = require_profile(ctx)?;
+
+        let lobby = open_lobby(ctx, LobbySettings {
+            chain_id,
+            match_id,
+            arena_address,
+            entry_fee,
+            max_players,
+            time_control,
+            is_locked: false,
+            visibility,
+            // Private lobbies block spectators unless the host opts in
+            allow_spectators: visibility != LobbyVisibility::Private,
+            max_spectators: None,
+            previous_lobby_id: None,
+            tournament_id: None,
+        }, vec![Seat {
+            identity: ctx.sender,
+            address: profile.primary_address,
+            name: profile.display_name,
+            color: None,
+            has_deposited: true, // Host pays on creation
+            joined_at: ctx.timestamp,
+        }], "created")?;
+        let lobby_id = lobby.id;
+        let room_code = lobby.room_code;
+
+        if let Some(passcode) = passcode {
+            set_passcode(ctx, &lobby_id, &passcode)?;
+        }
+
+        let created = LastCreatedLobby {
+            identity: ctx.sender,
+            lobby_id: lobby_id.clone(),
+            room_code: room_code.clone(),
+            created_at: ctx.timestamp,
+        };
+        if ctx.db.last_created_lobby().identity().find(ctx.sender).is_some() {
+            ctx.db.last_created_lobby().identity().update(created);
+        } else {
+            ctx.db.last_created_lobby().insert(created);
+        }
+
+        audit(ctx, "create_lobby", Some(&lobby_id), serde_json::json!({ "chain_id": chain_id, "match_id": match_id, "max_players": max_players, "room_code": room_code }), "ok");
+        log::info!("Lobby created: {} (code {}) by {:?}", lobby_id, room_code, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Join an existing lobby
+#[spacetimedb::reducer]
+pub fn join_lobby(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    passcode: Option<String>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "join_lobby", move || {
+        // Get lobby
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::LobbyNotAcceptingPlayers);
+        }
+        if lobby.is_locked {
+            return Err(ErrorCode::LobbyLocked);
+        }
+
+        // Check if already joined
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        if ctx.db.lobby_player().id().find(&player_id).is_some() {
+            return Err(ErrorCode::AlreadyInLobby);
+        }
+
+        let profile = require_profile(ctx)?;
+
+        // Find an open invite addressed to this player (by identity or wallet)
+        let open_invites: Vec<_> = ctx.db.lobby_invite()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|i| i.claimed_by.is_none() && i.expires_at > ctx.timestamp)
+            .collect();
+        let invite = open_invites.iter().find(|i| invite_targets(i, ctx.sender, &profile.primary_address));
+
+        // === REMATCH RESERVATION ===
+        // Until its invites expire, a rematch only seats the players it was opened for
+        if lobby.previous_lobby_id.is_some() && invite.is_none() && !open_invites.is_empty() {
+            return Err(ErrorCode::RematchReserved);
+        }
+
+        // === PRIVATE LOBBY CHECK ===
+        if (lobby.visibility == LobbyVisibility::Private || lobby.has_passcode) && invite.is_none() {
+            let passcode_ok = match (passcode, ctx.db.lobby_passcode().lobby_id().find(&lobby_id)) {
+                (Some(passcode), Some(stored)) => hash_passcode(&stored.salt, &passcode) == stored.passcode_hash,
+                _ => false,
+            };
+            if !passcode_ok {
+                return Err(ErrorCode::LobbyPrivate);
+            }
+        }
+
+        // Get current players
+        let players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .collect();
+
+        if players.len() >= lobby.max_players as usize {
+            return Err(ErrorCode::LobbyFull);
+        }
+
+        // Seats held for other players by unexpired invites are not available
+        let reserved_seats = count_reserved_seats(&open_invites, &players, Some((ctx.sender, &profile.primary_address)));
+        if players.len() + reserved_seats >= lobby.max_players as usize {
+            return Err(ErrorCode::SeatsReserved);
+        }
+
+        // Use up the invite
+        if let Some(invite) = invite {
+            ctx.db.lobby_invite().id().update(LobbyInvite {
+                claimed_by: Some(ctx.sender),
+                ..invite.clone()
+            });
+        }
+
+        // Assign color
+        let used_colors: std::collections::HashSet<String> = players.iter().map(|p| p.color.clone()).collect();
+        let available_color = assign_color(ctx, ctx.sender, &used_colors)?;
+
+        // Add player
+        ctx.db.lobby_player().insert(LobbyPlayer {
+            id: player_id.clone(),
+            lobby_id: lobby_id.clone(),
+            identity: ctx.sender,
+            address: profile.primary_address,
+            name: profile.display_name,
+            color: available_color,
+            is_host: false,
+            is_alive: true,
+            has_deposited: false,
+            is_ready: false,
+            time_bank_ms: initial_time_bank_ms(lobby.time_control),
+            eliminated_at_move: None,
+            fid: linked_fid(ctx, ctx.sender),
+            joined_at: ctx.timestamp,
+        });
+
+        // Update lobby timestamp
+        ctx.db.lobby().id().update(Lobby {
+            updated_at: ctx.timestamp,
+            ..lobby
+        });
+
+        // A spectator who takes a seat stops spectating
+        if let Some(spectator) = ctx.db.spectator().id().find(&player_id) {
+            remove_spectator(ctx, spectator);
+        }
+
+        audit(ctx, "join_lobby", Some(&lobby_id), serde_json::json!({}), "ok");
+        log::info!("Player {:?} joined lobby {}", ctx.sender, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Mark player as having deposited USDC on-chain
+#[spacetimedb::reducer]
+pub fn confirm_deposit(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    player_address: String,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "confirm_deposit", move || {
+        // Find player by address in this lobby
+        let player = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .find(|p| p.address.to_lowercase() == player_address.to_lowercase())
+            .ok_or(ErrorCode::PlayerNotInLobby)?;
+
+        // Only the owner of the paying wallet can vouch for its deposit
+        if player.identity != ctx.sender || !is_wallet_linked(ctx, &player_address, ctx.sender) {
+            return Err(ErrorCode::WalletNotLinked);
+        }
+
+        ctx.db.lobby_player().id().update(LobbyPlayer {
+            has_deposited: true,
+            ..player
+        });
+
+        audit(ctx, "confirm_deposit", Some(&lobby_id), serde_json::json!({ "player_address": player_address }), "ok");
+        log::info!("Deposit confirmed for {} in lobby {}", player_address, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Start the game (host only)
+#[spacetimedb::reducer]
+pub fn start_game(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "start_game", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+
+        let mut players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .collect();
+        players.sort_by_key(|p| p.joined_at);
+
+        if players.len() < 2 {
+            return Err(ErrorCode::NotEnoughPlayers);
+        }
+
+        // Check all players have deposited
+        if !players.iter().all(|p| p.has_deposited) {
+            return Err(ErrorCode::NotAllDeposited);
+        }
+
+        // Check all players are ready (the host starting the game counts as ready)
+        if !players.iter().all(|p| p.is_ready || p.is_host) {
+            return Err(ErrorCode::NotAllReady);
+        }
+
+        // Set initial turn deadline for the first player from the lobby's time control
+        let deadline = ctx.timestamp + turn_allowance(lobby.time_control, &players[0]);
+
+        // Update lobby status
+        transition_lobby(ctx, lobby, LobbyStatus::Live, "game_started")?;
+    
+        let game_state = ctx.db.game_state().lobby_id().find(&lobby_id).expect("open_lobby gives every lobby a game state");
+        ctx.db.game_state().lobby_id().update(GameState {
+            last_move_at: ctx.timestamp, // Clock starts now for the first player
+            turn_deadline: deadline,
+            ..game_state
+        });
+
+        // Everyone gets a stats row, even if they never get to move
+        for player in &players {
+            ctx.db.match_stats().insert(MatchStats {
+                id: player.id.clone(),
+                lobby_id: lobby_id.clone(),
+                identity: player.identity,
+                moves_made: 0,
+                explosions: 0,
+                longest_chain: 0,
+                cells_captured: 0,
+                peak_orbs: 0,
+                total_think_ms: 0,
+                avg_think_ms: 0,
+                is_final: false,
+                updated_at: ctx.timestamp,
+            });
+        }
+
+        audit(ctx, "start_game", Some(&lobby_id), serde_json::json!({}), "ok");
+        log::info!("Game started in lobby {}", lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Make a move (place orb)
+#[spacetimedb::reducer]
+pub fn make_move(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    row: u32,
+    col: u32,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "make_move", move || {
+        // Validate lobby
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Live {
+            return Err(ErrorCode::GameNotLive);
+        }
+
+        // Get game state
+        let game_state = ctx.db.game_state().lobby_id().find(&lobby_id)
+            .expect("open_lobby gives every lobby a game state");
+
+        // === TURN LOCK CHECK (Prevent race conditions) ===
+        if let Some(lock_time) = game_state.turn_lock_until {
+            if ctx.timestamp < lock_time {
+                return Err(ErrorCode::TurnLocked);
+            }
+        }
+
+        // === RATE LIMITING (Prevent spam) ===
+        if let Some(time_since_last) = ctx.timestamp.duration_since(game_state.last_move_at) {
+            if time_since_last < std::time::Duration::from_millis(300) {
+                return Err(ErrorCode::MoveTooFast);
+            }
+        }
+
+        // Get alive players sorted by join time
+        let mut players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|p| p.is_alive)
+            .collect();
+        players.sort_by_key(|p| p.joined_at);
+
+        if players.is_empty() {
+            return Err(ErrorCode::NoActivePlayers);
+        }
+
+        // Parse board
+        let mut board: Board = serde_json::from_str(&game_state.board_json).unwrap();
+
+        // When the current turn started (used to charge the chess clock)
+        let mut turn_started_at = game_state.last_move_at;
+
+        // === AUTO-TIMEOUT CHECK ===
+        // If current player's turn has expired, auto-eliminate them
+        if ctx.timestamp > game_state.turn_deadline {
+            let timed_out_idx = game_state.current_player_index as usize % players.len();
+            let timed_out_player = &players[timed_out_idx];
+        
+            log::info!("Player {} auto-timed out!", timed_out_player.name);
+        
+            // Eliminate timed-out player
+            let final_orbs = count_orbs(&board, &timed_out_player.color);
+            eliminate_player(ctx, timed_out_player, game_state.move_count, "timeout", final_orbs);
+
+            // The next player's turn began when the previous one expired
+            turn_started_at = game_state.turn_deadline;
+        
+            // Refresh alive players
+            players = ctx.db.lobby_player()
+                .lobby_id()
+                .filter(&lobby_id)
+                .filter(|p| p.is_alive)
+                .collect();
+            players.sort_by_key(|p| p.joined_at);
+        
+            // Check for winner after timeout
+            if players.len() == 1 {
+                let winner = &players[0];
+                finish_lobby(ctx, lobby, winner, &board, "timeout")?;
+                audit(ctx, "make_move", Some(&lobby_id), serde_json::json!({ "row": row, "col": col }), "opponent_timed_out_finished");
+                log::info!("Game finished by auto-timeout! Winner: {}", winner.name);
+                return Ok(()); // Exit early
+            }
+        
+            if players.is_empty() {
+                return Err(ErrorCode::NoActivePlayers);
+            }
+        }
+
+        // Check if it's this player's turn
+        let current_idx = game_state.current_player_index as usize % players.len();
+        let current_player = &players[current_idx];
+        if current_player.identity != ctx.sender {
+            return Err(ErrorCode::NotYourTurn);
+        }
+
+        // Time the mover spent on this turn
+        let think_ms = ctx.timestamp
+            .duration_since(turn_started_at)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        // === CHESS CLOCK FLAG ===
+        // A turn backdated by an auto-timeout can run past the mover's bank before the
+        // deadline is refreshed; the mover has flagged, so they lose on time instead of moving
+        if let TimeControl::Clock(clock) = lobby.time_control {
+            if charge_clock(current_player.time_bank_ms, think_ms, clock.increment_secs).is_none() {
+                let flagged_at = turn_started_at + std::time::Duration::from_millis(current_player.time_bank_ms);
+                let final_orbs = count_orbs(&board, &current_player.color);
+                eliminate_player(ctx, current_player, game_state.move_count, "timeout", final_orbs);
+                log::info!("Player {} ran out of clock time!", current_player.name);
+
+                let mut remaining: Vec<_> = ctx.db.lobby_player()
+                    .lobby_id()
+                    .filter(&lobby_id)
+                    .filter(|p| p.is_alive)
+                    .collect();
+                remaining.sort_by_key(|p| p.joined_at);
+
+                if remaining.len() == 1 {
+                    let winner = &remaining[0];
+                    finish_lobby(ctx, lobby, winner, &board, "timeout")?;
+                    audit(ctx, "make_move", Some(&lobby_id), serde_json::json!({ "row": row, "col": col }), "clock_expired_finished");
+                    return Ok(());
+                }
+
+                // The next player's turn began when the mover's bank ran out
+                let next_idx = current_idx % remaining.len();
+                let next_player = &remaining[next_idx];
+                ctx.db.game_state().lobby_id().update(GameState {
+                    current_player_index: next_idx as u32,
+                    last_move_at: flagged_at,
+                    turn_deadline: flagged_at + turn_allowance(lobby.time_control, next_player),
+                    turn_lock_until: None,
+                    ..game_state
+                });
+                audit(ctx, "make_move", Some(&lobby_id), serde_json::json!({ "row": row, "col": col }), "clock_expired");
+                migrate_host_if_eliminated(ctx, lobby);
+                return Ok(());
+            }
+        }
+
+        // Get board dimensions from game state
+        let rows = game_state.rows as usize;
+        let cols = game_state.cols as usize;
+    
+        // Validate move
+        let r = row as usize;
+        let c = col as usize;
+        if r >= rows || c >= cols {
+            return Err(ErrorCode::InvalidPosition);
+        }
+
+        let cell = &board[r][c];
+        if cell.owner.is_some() && cell.owner.as_ref() != Some(&current_player.color) {
+            return Err(ErrorCode::CellOwned);
+        }
+
+        // Place orb
+        let board_before = board.clone();
+        board[r][c].orbs += 1;
+        board[r][c].owner = Some(current_player.color.clone());
+
+        // Process chain reactions
+        let reaction = run_chain_reaction(&mut board, rows, cols);
+        track_move_achievements(ctx, &players, current_player, &board_before, &board, reaction.explosions);
+
+        record_move_stats(ctx, current_player, &reaction, count_orbs(&board, &current_player.color), think_ms);
+
+        // Count orbs per player
+        let mut player_orb_counts: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        for player in &players {
+            player_orb_counts.insert(player.color.clone(), 0);
+        }
+
+        for row in &board {
+            for cell in row {
+                if let Some(ref owner) = cell.owner {
+                    *player_orb_counts.entry(owner.clone()).or_insert(0) += cell.orbs;
+                }
+            }
+        }
+
+        // Check eliminations (only after first round)
+        if game_state.move_count >= players.len() as u32 {
+            for player in &players {
+                if *player_orb_counts.get(&player.color).unwrap_or(&0) == 0 && player.is_alive {
+                    eliminate_player(ctx, player, game_state.move_count, "orbs_lost", 0);
+                    log::info!("Player {} eliminated!", player.name);
+                }
+            }
+        }
+
+        // === CHESS CLOCK ===
+        // Charge the mover for the time spent on this turn, then add the increment
+        // (the flag check above guarantees the bank covered the think time)
+        if let TimeControl::Clock(clock) = lobby.time_control {
+            let mover = ctx.db.lobby_player().id().find(&current_player.id)
+                .expect("The mover is still seated");
+            if let Some(time_bank_ms) = charge_clock(mover.time_bank_ms, think_ms, clock.increment_secs) {
+                ctx.db.lobby_player().id().update(LobbyPlayer {
+                    time_bank_ms,
+                    ..mover
+                });
+            }
+        }
+
+        // Refresh alive players
+        let mut alive_players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|p| p.is_alive)
+            .collect();
+        alive_players.sort_by_key(|p| p.joined_at);
+
+        let total_orbs: u32 = player_orb_counts.values().sum();
+
+        // Check for winner
+        if alive_players.len() == 1 && total_orbs > 0 {
+            let winner = &alive_players[0];
+            finish_lobby(ctx, lobby, winner, &board, "last_player_standing")?;
+            audit(ctx, "make_move", Some(&lobby_id), serde_json::json!({ "row": row, "col": col }), "finished");
+            log::info!("Game finished! Winner: {} ({})", winner.name, winner.address);
+        
+            // Update final board state
+            ctx.db.game_state().lobby_id().update(GameState {
+                board_json: serde_json::to_string(&board).unwrap(),
+                move_count: game_state.move_count + 1,
+                last_move_at: ctx.timestamp,
+                turn_lock_until: None,
+                last_move_player: Some(ctx.sender),
+                ..game_state
+            });
+        
+            // CRITICAL: Return early - game is over, no more moves allowed
+            return Ok(());
+        }
+
+        // Record move
+        let move_id = format!("{}_{}", lobby_id, game_state.move_count);
+        ctx.db.game_move().insert(GameMove {
+            id: move_id,
+            lobby_id: lobby_id.clone(),
+            move_index: game_state.move_count,
+            player_identity: ctx.sender,
+            row,
+            col,
+            timestamp: ctx.timestamp,
+        });
+
+        // Update game state
+        let new_player_index = if !alive_players.is_empty() {
+            (game_state.current_player_index + 1) % alive_players.len() as u32
+        } else {
+            0
+        };
+
+        // Set new turn deadline for the next player from the lobby's time control
+        let new_deadline = match alive_players.get(new_player_index as usize) {
+            Some(next_player) => ctx.timestamp + turn_allowance(lobby.time_control, next_player),
+            None => ctx.timestamp + turn_duration(lobby.time_control),
+        };
+
+        ctx.db.game_state().lobby_id().update(GameState {
+            board_json: serde_json::to_string(&board).unwrap(),
+            rows: game_state.rows,
+            cols: game_state.cols,
+            current_player_index: new_player_index,
+            move_count: game_state.move_count + 1,
+            last_move_at: ctx.timestamp,
+            turn_deadline: new_deadline,
+            turn_lock_until: None, // Release lock
+            last_move_player: Some(ctx.sender), // Track last player
+            ..game_state
+        });
+
+        audit(ctx, "make_move", Some(&lobby_id), serde_json::json!({ "row": row, "col": col, "explosions": reaction.explosions }), "ok");
+
+        // An eliminated host hands over so the live game keeps an owner
+        migrate_host_if_eliminated(ctx, lobby);
+
+        Ok(())
+    })
+}
+
+/// Claim timeout for current player
+#[spacetimedb::reducer]
+pub fn claim_timeout(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "claim_timeout", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Live {
+            return Err(ErrorCode::GameNotLive);
+        }
+
+        let game_state = ctx.db.game_state().lobby_id().find(&lobby_id)
+            .expect("open_lobby gives every lobby a game state");
+
+        // Check if deadline passed
+        if ctx.timestamp < game_state.turn_deadline {
+            return Err(ErrorCode::TurnNotTimedOut);
+        }
+
+        // Get alive players sorted by join time
+        let mut players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|p| p.is_alive)
+            .collect();
+        players.sort_by_key(|p| p.joined_at);
+
+        if players.is_empty() {
+            return Err(ErrorCode::NoActivePlayers);
+        }
+
+        // Identify timed out player
+        let current_idx = game_state.current_player_index as usize % players.len();
+        let timed_out_player = &players[current_idx];
+
+        // Eliminate player
+        let board: Board = serde_json::from_str(&game_state.board_json).unwrap();
+        let final_orbs = count_orbs(&board, &timed_out_player.color);
+        eliminate_player(ctx, timed_out_player, game_state.move_count, "timeout", final_orbs);
+    
+        log::info!("Player {} timed out!", timed_out_player.name);
+
+        // Refresh alive players
+        let mut alive_players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|p| p.is_alive)
+            .collect();
+        alive_players.sort_by_key(|p| p.joined_at);
+
+        // Check for winner
+        if alive_players.len() == 1 {
+            let winner = &alive_players[0];
+            finish_lobby(ctx, lobby, winner, &board, "timeout")?;
+            audit(ctx, "claim_timeout", Some(&lobby_id), serde_json::json!({ "timed_out": timed_out_player.identity.to_hex().to_string() }), "finished");
+            log::info!("Game finished by timeout! Winner: {} ({})", winner.name, winner.address);
+        } else {
+            // Advance turn
+            let new_player_index = if !alive_players.is_empty() {
+                // We don't increment index because the current player was removed, 
+                // so the next player falls into the same index (modulo new length)
+                // But we need to be careful about the modulo logic.
+                // If we had [A, B, C] and B (index 1) timed out. New list [A, C].
+                // We want C to play. C is now at index 1. So index stays 1.
+                // If C (index 2) timed out. New list [A, B]. We want A to play. Index 0.
+                // So we just take current_index % new_length.
+                game_state.current_player_index % alive_players.len() as u32
+            } else {
+                0
+            };
+
+            // Set new turn deadline for the next player from the lobby's time control
+            let new_deadline = match alive_players.get(new_player_index as usize) {
+                Some(next_player) => ctx.timestamp + turn_allowance(lobby.time_control, next_player),
+                None => ctx.timestamp + turn_duration(lobby.time_control),
+            };
+
+            ctx.db.game_state().lobby_id().update(GameState {
+                current_player_index: new_player_index,
+                last_move_at: ctx.timestamp, // Next player's clock starts now
+                turn_deadline: new_deadline,
+                ..game_state
+            });
+
+            audit(ctx, "claim_timeout", Some(&lobby_id), serde_json::json!({ "timed_out": timed_out_player.identity.to_hex().to_string() }), "ok");
+
+            // An eliminated host hands over so the live game keeps an owner
+            migrate_host_if_eliminated(ctx, lobby);
+        }
+
+        Ok(())
+    })
+}
+
+/// Resign from a live game
+#[spacetimedb::reducer]
+pub fn resign(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "resign", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Live {
+            return Err(ErrorCode::GameNotLive);
+        }
+
+        let game_state = ctx.db.game_state().lobby_id().find(&lobby_id)
+            .expect("open_lobby gives every lobby a game state");
+
+        // Get alive players sorted by join time
+        let mut players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|p| p.is_alive)
+            .collect();
+        players.sort_by_key(|p| p.joined_at);
+
+        let resigned_idx = players.iter()
+            .position(|p| p.identity == ctx.sender)
+            .ok_or(ErrorCode::NotAnActivePlayer)?;
+        let current_idx = game_state.current_player_index as usize % players.len();
+
+        let board: Board = serde_json::from_str(&game_state.board_json).unwrap();
+        let resigned_player = players.remove(resigned_idx);
+        let final_orbs = count_orbs(&board, &resigned_player.color);
+        eliminate_player(ctx, &resigned_player, game_state.move_count, "resign", final_orbs);
+
+        log::info!("Player {} resigned!", resigned_player.name);
+
+        // Check for winner
+        if players.len() == 1 {
+            let winner = &players[0];
+            finish_lobby(ctx, lobby, winner, &board, "resignation")?;
+            audit(ctx, "resign", Some(&lobby_id), serde_json::json!({}), "finished");
+            log::info!("Game finished by resignation! Winner: {} ({})", winner.name, winner.address);
+            return Ok(());
+        }
+
+        // Keep the turn with the same player, or pass it on if the resigner was to move
+        let (new_player_index, new_deadline) = if resigned_idx == current_idx {
+            let next_idx = current_idx % players.len();
+            let deadline = ctx.timestamp + turn_allowance(lobby.time_control, &players[next_idx]);
+            (next_idx as u32, deadline)
+        } else if resigned_idx < current_idx {
+            ((current_idx - 1) as u32, game_state.turn_deadline)
+        } else {
+            (current_idx as u32, game_state.turn_deadline)
+        };
+        let last_move_at = if resigned_idx == current_idx { ctx.timestamp } else { game_state.last_move_at };
+
+        ctx.db.game_state().lobby_id().update(GameState {
+            current_player_index: new_player_index,
+            last_move_at,
+            turn_deadline: new_deadline,
+            ..game_state
+        });
+
+        audit(ctx, "resign", Some(&lobby_id), serde_json::json!({}), "ok");
+
+        // A resigned host hands over so the live game keeps an owner
+        migrate_host_if_eliminated(ctx, lobby);
+
+        Ok(())
+    })
+}
+
+/// Leave lobby (before game starts)
+#[spacetimedb::reducer]
+pub fn leave_lobby(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "leave_lobby", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+        if lobby.tournament_id.is_some() {
+            return Err(ErrorCode::TournamentSeat);
+        }
+
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let player = ctx.db.lobby_player().id().find(&player_id)
+            .ok_or(ErrorCode::NotInLobby)?;
+
+        // Whatever they paid goes back to them
+        refund_deposit(ctx, &lobby, &player, "left");
+
+        if player.is_host {
+            ctx.db.lobby_player().id().delete(&player_id);
+
+            // Pass the host role on; cancel only when nobody is left
+            match migrate_host(ctx, lobby, ctx.sender) {
+                Some(lobby) => {
+                    audit(ctx, "leave_lobby", Some(&lobby_id), serde_json::json!({ "new_host": lobby.host_identity.to_hex().to_string() }), "host_migrated");
+                    log::info!("Host left lobby {}, new host {:?}", lobby_id, lobby.host_identity);
+                }
+                None => {
+                    let lobby = ctx.db.lobby().id().find(&lobby_id)
+                        .ok_or(ErrorCode::LobbyNotFound)?;
+                    transition_lobby(ctx, lobby, LobbyStatus::Cancelled, "host_left")?;
+
+                    audit(ctx, "leave_lobby", Some(&lobby_id), serde_json::json!({}), "cancelled");
+                    log::info!("Lobby {} cancelled, host left and nobody remains", lobby_id);
+                }
+            }
+        } else {
+            ctx.db.lobby_player().id().delete(&player_id);
+
+            ctx.db.lobby().id().update(Lobby {
+                updated_at: ctx.timestamp,
+                ..lobby
+            });
+
+            audit(ctx, "leave_lobby", Some(&lobby_id), serde_json::json!({}), "ok");
+            log::info!("Player {:?} left lobby {}", ctx.sender, lobby_id);
+        }
+
+        Ok(())
+    })
+}
+
+/// Pick a different colour in a waiting lobby
+#[spacetimedb::reducer]
+pub fn choose_color(ctx: &ReducerContext, lobby_id: String, color: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "choose_color", move || {
+        if !PLAYER_COLORS.contains(&color.as_str()) {
+            return Err(ErrorCode::UnknownColour);
+        }
+
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let player = ctx.db.lobby_player().id().find(&player_id)
+            .ok_or(ErrorCode::NotInLobby)?;
+
+        if player.color == color {
+            return Ok(());
+        }
+
+        let taken = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&lobby_id)
+            .any(|p| p.color == color);
+        if taken {
+            return Err(ErrorCode::ColourTaken);
+        }
+
+        ctx.db.lobby_player().id().update(LobbyPlayer {
+            color: color.clone(),
+            ..player
+        });
+
+        audit(ctx, "choose_color", Some(&lobby_id), serde_json::json!({ "color": color }), "ok");
+        log::info!("Player {:?} chose {} in lobby {}", ctx.sender, color, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Mark yourself ready (or not) in a waiting lobby
+#[spacetimedb::reducer]
+pub fn set_ready(ctx: &ReducerContext, lobby_id: String, ready: bool) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_ready", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let player = ctx.db.lobby_player().id().find(&player_id)
+            .ok_or(ErrorCode::NotInLobby)?;
+
+        ctx.db.lobby_player().id().update(LobbyPlayer {
+            is_ready: ready,
+            ..player
+        });
+
+        audit(ctx, "set_ready", Some(&lobby_id), serde_json::json!({ "ready": ready }), "ok");
+        log::info!("Player {:?} ready={} in lobby {}", ctx.sender, ready, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Remove a player from a waiting lobby (host only)
+#[spacetimedb::reducer]
+pub fn kick_player(ctx: &ReducerContext, lobby_id: String, player_identity: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "kick_player", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+        if lobby.tournament_id.is_some() {
+            return Err(ErrorCode::TournamentSeat);
+        }
+        if player_identity == ctx.sender {
+            return Err(ErrorCode::CannotKickSelf);
+        }
+
+        let player_id = format!("{}_{:?}", lobby_id, player_identity);
+        let player = ctx.db.lobby_player().id().find(&player_id)
+            .ok_or(ErrorCode::PlayerNotInLobby)?;
+
+        ctx.db.lobby_player().id().delete(&player_id);
+
+        // Flag the entry fee for refund if they had already paid
+        refund_deposit(ctx, &lobby, &player, "kicked");
+
+        ctx.db.lobby().id().update(Lobby {
+            updated_at: ctx.timestamp,
+            ..lobby
+        });
+
+        audit(ctx, "kick_player", Some(&lobby_id), serde_json::json!({ "player": player_identity.to_hex().to_string() }), "ok");
+        log::info!("Player {:?} kicked from lobby {}", player_identity, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Lock or unlock a waiting lobby to new joins (host only)
+#[spacetimedb::reducer]
+pub fn lock_lobby(ctx: &ReducerContext, lobby_id: String, locked: bool) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "lock_lobby", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+
+        ctx.db.lobby().id().update(Lobby {
+            is_locked: locked,
+            updated_at: ctx.timestamp,
+            ..lobby
+        });
+
+        audit(ctx, "lock_lobby", Some(&lobby_id), serde_json::json!({ "locked": locked }), "ok");
+        log::info!("Lobby {} locked={}", lobby_id, locked);
+
+        Ok(())
+    })
+}
+
+/// Change who can join a waiting lobby (host only).
+/// A passcode replaces any existing one; None removes it.
+#[spacetimedb::reducer]
+pub fn set_lobby_visibility(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    visibility: LobbyVisibility,
+    passcode: Option<String>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_lobby_visibility", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+
+        // Becoming private blocks spectators unless the host opts back in
+        let allow_spectators = if visibility == LobbyVisibility::Private && lobby.visibility != LobbyVisibility::Private {
+            false
+        } else {
+            lobby.allow_spectators
+        };
+
+        ctx.db.lobby_passcode().lobby_id().delete(&lobby_id);
+        ctx.db.lobby().id().update(Lobby {
+            visibility,
+            has_passcode: false,
+            allow_spectators,
+            updated_at: ctx.timestamp,
+            ..lobby
+        });
+
+        if let Some(passcode) = passcode {
+            set_passcode(ctx, &lobby_id, &passcode)?;
+        }
+
+        audit(ctx, "set_lobby_visibility", Some(&lobby_id), serde_json::json!({ "visibility": format!("{:?}", visibility) }), "ok");
+        log::info!("Lobby {} visibility set to {:?}", lobby_id, visibility);
+
+        Ok(())
+    })
+}
+
+/// Reserve a seat for a wallet address and/or identity (host only)
+#[spacetimedb::reducer]
+pub fn invite_to_lobby(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    address: Option<String>,
+    identity: Option<Identity>,
+    ttl_minutes: u32,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "invite_to_lobby", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+        if address.is_none() && identity.is_none() {
+            return Err(ErrorCode::InviteTargetMissing);
+        }
+        if ttl_minutes == 0 || ttl_minutes > MAX_INVITE_TTL_MINUTES {
+            return Err(ErrorCode::InvalidInviteDuration);
+        }
+
+        let address = address.map(|a| a.to_lowercase());
+        let players: Vec<_> = ctx.db.lobby_player().lobby_id().filter(&lobby_id).collect();
+        let open_invites: Vec<_> = ctx.db.lobby_invite()
+            .lobby_id()
+            .filter(&lobby_id)
+            .filter(|i| i.claimed_by.is_none() && i.expires_at > ctx.timestamp)
+            .collect();
+
+        // One open invite per player
+        let already_invited = open_invites.iter().any(|i| {
+            (identity.is_some() && i.identity == identity) || (address.is_some() && i.address == address)
+        });
+        if already_invited {
+            return Err(ErrorCode::AlreadyInvited);
+        }
+
+        if players.len() + count_reserved_seats(&open_invites, &players, None) >= lobby.max_players as usize {
+            return Err(ErrorCode::NoFreeSeats);
+        }
+
+        ctx.db.lobby_invite().insert(LobbyInvite {
+            id: 0,
+            lobby_id: lobby_id.clone(),
+            address,
+            identity,
+            invited_by: ctx.sender,
+            created_at: ctx.timestamp,
+            expires_at: ctx.timestamp + std::time::Duration::from_secs(ttl_minutes as u64 * 60),
+            claimed_by: None,
+        });
+
+        audit(ctx, "invite_to_lobby", Some(&lobby_id), serde_json::json!({}), "ok");
+        log::info!("Invite created for lobby {}", lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Cancel an invite and release its seat (host only)
+#[spacetimedb::reducer]
+pub fn revoke_invite(ctx: &ReducerContext, invite_id: u64) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "revoke_invite", move || {
+        let invite = ctx.db.lobby_invite().id().find(invite_id)
+            .ok_or(ErrorCode::InviteNotFound)?;
+
+        let lobby = ctx.db.lobby().id().find(&invite.lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+
+        ctx.db.lobby_invite().id().delete(invite_id);
+
+        audit(ctx, "revoke_invite", Some(&invite.lobby_id), serde_json::json!({ "invite_id": invite_id }), "ok");
+        log::info!("Invite {} revoked in lobby {}", invite_id, invite.lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Hand the host role to another player in the lobby (host only)
+#[spacetimedb::reducer]
+pub fn transfer_host(ctx: &ReducerContext, lobby_id: String, new_host: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "transfer_host", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting && lobby.status != LobbyStatus::Live {
+            return Err(ErrorCode::LobbyNotActive);
+        }
+        if new_host == ctx.sender {
+            return Err(ErrorCode::AlreadyHost);
+        }
+
+        let new_host_id = format!("{}_{:?}", lobby_id, new_host);
+        let new_host_player = ctx.db.lobby_player().id().find(&new_host_id)
+            .ok_or(ErrorCode::PlayerNotInLobby)?;
+
+        if !new_host_player.is_alive {
+            return Err(ErrorCode::PlayerEliminated);
+        }
+
+        set_host(ctx, lobby, new_host_player);
+
+        audit(ctx, "transfer_host", Some(&lobby_id), serde_json::json!({ "new_host": new_host.to_hex().to_string() }), "ok");
+        log::info!("Host of lobby {} transferred to {:?}", lobby_id, new_host);
+
+        Ok(())
+    })
+}
+
+/// Get all lobbies (for listing)
+#[spacetimedb::reducer]
+pub fn ping(ctx: &ReducerContext) {
+    log::info!("Ping from {:?}", ctx.sender);
+}
+
+// ============================================================================
+// SPECTATOR REDUCERS
+// ============================================================================
+
+/// Watch a lobby as a spectator
+#[spacetimedb::reducer]
+pub fn join_as_spectator(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "join_as_spectator", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Waiting && lobby.status != LobbyStatus::Live {
+            return Err(ErrorCode::LobbyNotActive);
+        }
+        if !lobby.allow_spectators {
+            return Err(ErrorCode::SpectatorsNotAllowed);
+        }
+
+        // Players watch their own game, they don't spectate it
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        if ctx.db.lobby_player().id().find(&player_id).is_some() {
+            return Err(ErrorCode::PlayerCannotSpectate);
+        }
+
+        let spectator_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        if ctx.db.spectator().id().find(&spectator_id).is_some() {
+            return Err(ErrorCode::AlreadySpectating);
+        }
+
+        if let Some(max_spectators) = lobby.max_spectators {
+            if lobby.spectator_count >= max_spectators {
+                return Err(ErrorCode::SpectatorLimitReached);
+            }
+        }
+
+        ctx.db.spectator().insert(Spectator {
+            id: spectator_id,
+            lobby_id: lobby_id.clone(),
+            identity: ctx.sender,
+            joined_at: ctx.timestamp,
+        });
+
+        ctx.db.lobby().id().update(Lobby {
+            spectator_count: lobby.spectator_count + 1,
+            ..lobby
+        });
+
+        audit(ctx, "join_as_spectator", Some(&lobby_id), serde_json::json!({}), "ok");
+        log::info!("Spectator {:?} joined lobby {}", ctx.sender, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Stop watching a lobby
+#[spacetimedb::reducer]
+pub fn leave_spectating(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "leave_spectating", move || {
+        let spectator_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let spectator = ctx.db.spectator().id().find(&spectator_id)
+            .ok_or(ErrorCode::NotSpectating)?;
+
+        remove_spectator(ctx, spectator);
+
+        audit(ctx, "leave_spectating", Some(&lobby_id), serde_json::json!({}), "ok");
+        log::info!("Spectator {:?} left lobby {}", ctx.sender, lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Configure who may spectate a lobby (host only)
+#[spacetimedb::reducer]
+pub fn set_spectator_policy(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    allow_spectators: bool,
+    max_spectators: Option<u32>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_spectator_policy", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+
+        // Turning spectators off removes everyone currently watching
+        let mut spectator_count = lobby.spectator_count;
+        if !allow_spectators {
+            let spectators: Vec<_> = ctx.db.spectator()
+                .lobby_id()
+                .filter(&lobby_id)
+                .collect();
+
+            for spectator in spectators {
+                ctx.db.spectator().id().delete(&spectator.id);
+            }
+            spectator_count = 0;
+        }
+
+        ctx.db.lobby().id().update(Lobby {
+            allow_spectators,
+            max_spectators,
+            spectator_count,
+            updated_at: ctx.timestamp,
+            ..lobby
+        });
+
+        audit(ctx, "set_spectator_policy", Some(&lobby_id), serde_json::json!({ "allow_spectators": allow_spectators, "max_spectators": max_spectators }), "ok");
+        log::info!("Spectator policy updated for lobby {}", lobby_id);
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// CHAT REDUCERS
+// ============================================================================
+
+/// Send a chat message to a lobby (players and spectators only)
+#[spacetimedb::reducer]
+pub fn send_chat(ctx: &ReducerContext, lobby_id: String, text: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "send_chat", move || {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ErrorCode::MessageEmpty);
+        }
+        if text.chars().count() > MAX_CHAT_LENGTH {
+            return Err(ErrorCode::MessageTooLong);
+        }
+
+        // Sender must be seated in the lobby or watching it
+        let member_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let sender_name = if let Some(player) = ctx.db.lobby_player().id().find(&member_id) {
+            player.name
+        } else if ctx.db.spectator().id().find(&member_id).is_some() {
+            format!("Spectator {}", ctx.sender.to_abbreviated_hex())
+        } else {
+            return Err(ErrorCode::NotInChat);
+        };
+
+        // === MUTE CHECK ===
+        if let Some(mute) = ctx.db.chat_mute().identity().find(ctx.sender) {
+            match mute.muted_until {
+                Some(until) if ctx.timestamp >= until => {
+                    ctx.db.chat_mute().identity().delete(ctx.sender);
+                }
+                _ => return Err(ErrorCode::Muted),
+            }
+        }
+
+        // === RATE LIMITING ===
+        let window = std::time::Duration::from_secs(CHAT_RATE_WINDOW_SECS);
+        match ctx.db.chat_rate_limit().identity().find(ctx.sender) {
+            Some(limit) if ctx.timestamp < limit.window_start + window => {
+                if limit.message_count >= CHAT_RATE_LIMIT {
+                    return Err(ErrorCode::ChatRateLimited);
+                }
+                ctx.db.chat_rate_limit().identity().update(ChatRateLimit {
+                    message_count: limit.message_count + 1,
+                    ..limit
+                });
+            }
+            Some(limit) => {
+                ctx.db.chat_rate_limit().identity().update(ChatRateLimit {
+                    window_start: ctx.timestamp,
+                    message_count: 1,
+                    ..limit
+                });
+            }
+            None => {
+                ctx.db.chat_rate_limit().insert(ChatRateLimit {
+                    identity: ctx.sender,
+                    window_start: ctx.timestamp,
+                    message_count: 1,
+                });
+            }
+        }
+
+        let message = ctx.db.chat_message().insert(ChatMessage {
+            id: 0,
+            lobby_id: lobby_id.clone(),
+            sender: ctx.sender,
+            sender_name,
+            text: filter_chat_text(text),
+            sent_at: ctx.timestamp,
+        });
+
+        audit(ctx, "send_chat", Some(&lobby_id), serde_json::json!({ "message_id": message.id }), "ok");
+
+        Ok(())
+    })
+}
+
+/// Delete a chat message (moderators, or the host of the lobby)
+#[spacetimedb::reducer]
+pub fn delete_chat_message(ctx: &ReducerContext, message_id: u64) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "delete_chat_message", move || {
+        let message = ctx.db.chat_message().id().find(message_id)
+            .ok_or(ErrorCode::MessageNotFound)?;
+
+        let is_lobby_host = ctx.db.lobby().id().find(&message.lobby_id)
+            .is_some_and(|lobby| lobby.host_identity == ctx.sender);
+        if !is_lobby_host && !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+
+        ctx.db.chat_message().id().delete(message_id);
+
+        audit(ctx, "delete_chat_message", None, serde_json::json!({ "message_id": message_id }), "ok");
+        log::info!("Chat message {} deleted by {:?}", message_id, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Mute an identity from chat (moderators only)
+#[spacetimedb::reducer]
+pub fn mute_user(ctx: &ReducerContext, identity: Identity, duration_minutes: Option<u32>) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "mute_user", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+        if is_moderator(ctx, identity) {
+            return Err(ErrorCode::CannotMuteModerator);
+        }
+
+        let muted_until = duration_minutes
+            .map(|minutes| ctx.timestamp + std::time::Duration::from_secs(minutes as u64 * 60));
+
+        let mute = ChatMute {
+            identity,
+            muted_by: ctx.sender,
+            muted_until,
+            created_at: ctx.timestamp,
+        };
+        if ctx.db.chat_mute().identity().find(identity).is_some() {
+            ctx.db.chat_mute().identity().update(mute);
+        } else {
+            ctx.db.chat_mute().insert(mute);
+        }
+
+        audit(ctx, "mute_user", None, serde_json::json!({ "identity": identity.to_hex().to_string() }), "ok");
+        log::info!("{:?} muted by {:?}", identity, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Lift a chat mute (moderators only)
+#[spacetimedb::reducer]
+pub fn unmute_user(ctx: &ReducerContext, identity: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "unmute_user", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+
+        if !ctx.db.chat_mute().identity().delete(identity) {
+            return Err(ErrorCode::NotMuted);
+        }
+
+        audit(ctx, "unmute_user", None, serde_json::json!({ "identity": identity.to_hex().to_string() }), "ok");
+        log::info!("{:?} unmuted by {:?}", identity, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Grant moderator rights (moderators only)
+#[spacetimedb::reducer]
+pub fn add_moderator(ctx: &ReducerContext, identity: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "add_moderator", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+        if is_moderator(ctx, identity) {
+            return Err(ErrorCode::AlreadyModerator);
+        }
+
+        ctx.db.moderator().insert(Moderator {
+            identity,
+            added_by: ctx.sender,
+            added_at: ctx.timestamp,
+        });
+
+        audit(ctx, "add_moderator", None, serde_json::json!({ "identity": identity.to_hex().to_string() }), "ok");
+        log::info!("Moderator {:?} added by {:?}", identity, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Revoke moderator rights (moderators only)
+#[spacetimedb::reducer]
+pub fn remove_moderator(ctx: &ReducerContext, identity: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "remove_moderator", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+        if identity == ctx.sender {
+            return Err(ErrorCode::CannotRemoveSelf);
+        }
+
+        if !ctx.db.moderator().identity().delete(identity) {
+            return Err(ErrorCode::NotAModerator);
+        }
+
+        audit(ctx, "remove_moderator", None, serde_json::json!({ "identity": identity.to_hex().to_string() }), "ok");
+        log::info!("Moderator {:?} removed by {:?}", identity, ctx.sender);
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// REMATCH REDUCERS
+// ============================================================================
+
+/// Request a rematch of a finished lobby with the same settings and players.
+/// The requester hosts the new lobby; `match_id` is the new on-chain match.
+/// Every other player gets an invite, and nobody else can join until those expire.
+#[spacetimedb::reducer]
+pub fn request_rematch(
+    ctx: &ReducerContext,
+    lobby_id: String,
+    match_id: u64,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "request_rematch", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if !has_result(lobby.status) {
+            return Err(ErrorCode::LobbyNotFinished);
+        }
+        if lobby.rematch_lobby_id.is_some() {
+            return Err(ErrorCode::RematchAlreadyRequested);
+        }
+
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let requester = ctx.db.lobby_player().id().find(&player_id)
+            .ok_or(ErrorCode::NotInLobby)?;
+
+        // Seat them from their current profile, like join_lobby does
+        let profile = require_profile(ctx)?;
+
+        let now = ctx.timestamp;
+
+        // Same settings as the finished lobby, hosted by the requester, who keeps
+        // their colour; deposits still go through confirm_deposit
+        let rematch = open_lobby(ctx, LobbySettings {
+            chain_id: lobby.chain_id,
+            match_id,
+            arena_address: lobby.arena_address.clone(),
+            entry_fee: lobby.entry_fee,
+            max_players: lobby.max_players,
+            time_control: lobby.time_control,
+            is_locked: false,
+            visibility: lobby.visibility,
+            allow_spectators: lobby.allow_spectators,
+            max_spectators: lobby.max_spectators,
+            previous_lobby_id: Some(lobby_id.clone()),
+            tournament_id: None,
+        }, vec![Seat {
+            identity: ctx.sender,
+            address: profile.primary_address,
+            name: profile.display_name,
+            color: Some(requester.color),
+            has_deposited: false,
+            joined_at: now,
+        }], "rematch")?;
+        let new_lobby_id = rematch.id.clone();
+
+        // Hold a seat for every other player of the finished lobby
+        let expires_at = now + std::time::Duration::from_secs(REMATCH_INVITE_TTL_MINUTES * 60);
+        for player in ctx.db.lobby_player().lobby_id().filter(&lobby_id) {
+            if player.identity == ctx.sender {
+                continue;
+            }
+            ctx.db.lobby_invite().insert(LobbyInvite {
+                id: 0,
+                lobby_id: new_lobby_id.clone(),
+                address: Some(player.address.to_lowercase()),
+                identity: Some(player.identity),
+                invited_by: ctx.sender,
+                created_at: now,
+                expires_at,
+                claimed_by: None,
+            });
+        }
+
+        // Carry the passcode over so the rematch stays private
+        if let Some(passcode) = ctx.db.lobby_passcode().lobby_id().find(&lobby_id) {
+            ctx.db.lobby_passcode().insert(LobbyPasscode {
+                lobby_id: new_lobby_id.clone(),
+                ..passcode
+            });
+            ctx.db.lobby().id().update(Lobby {
+                has_passcode: true,
+                ..rematch
+            });
+        }
+
+        // Link the finished lobby forward to its rematch
+        ctx.db.lobby().id().update(Lobby {
+            rematch_lobby_id: Some(new_lobby_id.clone()),
+            updated_at: now,
+            ..lobby
+        });
+
+        audit(ctx, "request_rematch", Some(&lobby_id), serde_json::json!({ "rematch_lobby_id": new_lobby_id, "match_id": match_id }), "ok");
+        log::info!("Rematch {} requested for lobby {} by {:?}", new_lobby_id, lobby_id, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Accept a rematch: join the linked lobby with the same colour as before
+#[spacetimedb::reducer]
+pub fn accept_rematch(ctx: &ReducerContext, lobby_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "accept_rematch", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        let rematch_lobby_id = lobby.rematch_lobby_id
+            .ok_or(ErrorCode::RematchNotFound)?;
+
+        let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
+        let previous = ctx.db.lobby_player().id().find(&player_id)
+            .ok_or(ErrorCode::NotInLobby)?;
+
+        let rematch = ctx.db.lobby().id().find(&rematch_lobby_id)
+            .ok_or(ErrorCode::RematchNotFound)?;
+
+        if rematch.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::RematchClosed);
+        }
+        if rematch.is_locked {
+            return Err(ErrorCode::LobbyLocked);
+        }
+
+        let rematch_player_id = format!("{}_{:?}", rematch_lobby_id, ctx.sender);
+        if ctx.db.lobby_player().id().find(&rematch_player_id).is_some() {
+            return Err(ErrorCode::AlreadyInLobby);
+        }
+
+        // Seat them from their current profile, like join_lobby does
+        let profile = require_profile(ctx)?;
+
+        let players: Vec<_> = ctx.db.lobby_player()
+            .lobby_id()
+            .filter(&rematch_lobby_id)
+            .collect();
+
+        if players.len() >= rematch.max_players as usize {
+            return Err(ErrorCode::LobbyFull);
+        }
+
+        // Keep the previous colour unless someone else has taken it since
+        let used_colors: std::collections::HashSet<String> = players.iter().map(|p| p.color.clone()).collect();
+        let color = if used_colors.contains(&previous.color) {
+            assign_color(ctx, ctx.sender, &used_colors)?
+        } else {
+            previous.color
+        };
+
+        ctx.db.lobby_player().insert(LobbyPlayer {
+            id: rematch_player_id.clone(),
+            lobby_id: rematch_lobby_id.clone(),
+            identity: ctx.sender,
+            address: profile.primary_address,
+            name: profile.display_name,
+            color,
+            is_host: false,
+            is_alive: true,
+            has_deposited: false,
+            is_ready: false,
+            time_bank_ms: initial_time_bank_ms(rematch.time_control),
+            eliminated_at_move: None,
+            fid: linked_fid(ctx, ctx.sender),
+            joined_at: ctx.timestamp,
+        });
+
+        // Use up the seat the rematch held for this player
+        if let Some(invite) = ctx.db.lobby_invite()
+            .lobby_id()
+            .filter(&rematch_lobby_id)
+            .find(|i| i.identity == Some(ctx.sender) && i.claimed_by.is_none())
+        {
+            ctx.db.lobby_invite().id().update(LobbyInvite {
+                claimed_by: Some(ctx.sender),
+                ..invite
+            });
+        }
+
+        ctx.db.lobby().id().update(Lobby {
+            updated_at: ctx.timestamp,
+            ..rematch
+        });
+
+        // A spectator who takes a seat stops spectating
+        if let Some(spectator) = ctx.db.spectator().id().find(&rematch_player_id) {
+            remove_spectator(ctx, spectator);
+        }
+
+        audit(ctx, "accept_rematch", Some(&rematch_lobby_id), serde_json::json!({ "previous_lobby_id": lobby_id }), "ok");
+        log::info!("Player {:?} accepted rematch {}", ctx.sender, rematch_lobby_id);
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// PROFILE REDUCERS
+// ============================================================================
+
+/// Issue a fresh nonce for the caller to include in a sign-in message
+#[spacetimedb::reducer]
+pub fn request_wallet_nonce(ctx: &ReducerContext) {
+    let row = WalletNonce {
+        identity: ctx.sender,
+        nonce: format!("{:016x}{:016x}", ctx.rng().gen::<u64>(), ctx.rng().gen::<u64>()),
+        issued_at: ctx.timestamp,
+        expires_at: ctx.timestamp + std::time::Duration::from_secs(WALLET_NONCE_TTL_SECS),
+    };
+
+    if ctx.db.wallet_nonce().identity().find(ctx.sender).is_some() {
+        ctx.db.wallet_nonce().identity().update(row);
+    } else {
+        ctx.db.wallet_nonce().insert(row);
+    }
+
+    audit(ctx, "request_wallet_nonce", None, serde_json::json!({}), "ok");
+}
+
+/// Link a wallet to the caller by verifying a signed SIWE (EIP-4361) message
+/// for the wallet address, issued by SIWE_DOMAIN on one of SIWE_CHAIN_IDS and
+/// carrying the nonce from request_wallet_nonce. The signature is the 65-byte
+/// personal_sign hex.
+#[spacetimedb::reducer]
+pub fn link_wallet(ctx: &ReducerContext, address: String, message: String, signature: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "link_wallet", move || {
+        let address = normalize_address(&address)?;
+
+        let nonce = ctx.db.wallet_nonce().identity().find(ctx.sender)
+            .ok_or(ErrorCode::NonceNotFound)?;
+        if ctx.timestamp > nonce.expires_at {
+            return Err(ErrorCode::NonceExpired);
+        }
+
+        let siwe = parse_siwe_message(&message).ok_or(ErrorCode::InvalidSiweMessage)?;
+        if siwe.version != "1" {
+            return Err(ErrorCode::InvalidSiweMessage);
+        }
+        if siwe.address != address {
+            return Err(ErrorCode::MessageMismatch);
+        }
+        if siwe.nonce != nonce.nonce {
+            return Err(ErrorCode::NonceMissing);
+        }
+        if !siwe.is_for_our_site() {
+            return Err(ErrorCode::SiweDomainMismatch);
+        }
+        if !SIWE_CHAIN_IDS.contains(&siwe.chain_id) {
+            return Err(ErrorCode::SiweChainNotAllowed);
+        }
+
+        let signer = recover_personal_sign_address(&message, &signature)
+            .ok_or(ErrorCode::InvalidSignature)?;
+        if signer != address {
+            return Err(ErrorCode::SignatureMismatch);
+        }
+
+        // Nonces are single use
+        ctx.db.wallet_nonce().identity().delete(ctx.sender);
+
+        // A wallet proven from a new identity moves over to it
+        let link = WalletLink {
+            address: address.clone(),
+            identity: ctx.sender,
+            linked_at: ctx.timestamp,
+        };
+        if ctx.db.wallet_link().address().find(&address).is_some() {
+            ctx.db.wallet_link().address().update(link);
+        } else {
+            ctx.db.wallet_link().insert(link);
+        }
+
+        audit(ctx, "link_wallet", None, serde_json::json!({ "address": address }), "ok");
+        log::info!("Wallet {} linked to {:?}", address, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Link a Farcaster account with a Sign In With Farcaster message: a SIWE
+/// message for SIWE_DOMAIN carrying the nonce from request_wallet_nonce and
+/// the resource "farcaster://fid/<fid>", signed by the custody address the
+/// oracle has recorded for the FID. Username and avatar come from that record.
+#[spacetimedb::reducer]
+pub fn link_farcaster(
+    ctx: &ReducerContext,
+    fid: u64,
+    message: String,
+    signature: String,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "link_farcaster", move || {
+        let nonce = ctx.db.wallet_nonce().identity().find(ctx.sender)
+            .ok_or(ErrorCode::NonceNotFound)?;
+        if ctx.timestamp > nonce.expires_at {
+            return Err(ErrorCode::NonceExpired);
+        }
+
+        let siwe = parse_siwe_message(&message).ok_or(ErrorCode::InvalidSiweMessage)?;
+        if siwe.version != "1" {
+            return Err(ErrorCode::InvalidSiweMessage);
+        }
+        if siwe.nonce != nonce.nonce {
+            return Err(ErrorCode::NonceMissing);
+        }
+        if !siwe.is_for_our_site() {
+            return Err(ErrorCode::SiweDomainMismatch);
+        }
+        if siwe.chain_id != FARCASTER_CHAIN_ID {
+            return Err(ErrorCode::SiweChainNotAllowed);
+        }
+        if !siwe.resources.contains(&format!("farcaster://fid/{}", fid)) {
+            return Err(ErrorCode::MessageMismatch);
+        }
+
+        let signer = recover_personal_sign_address(&message, &signature)
+            .ok_or(ErrorCode::InvalidSignature)?;
+        if signer != siwe.address {
+            return Err(ErrorCode::SignatureMismatch);
+        }
+
+        // Only the FID's custody address can claim it
+        let account = ctx.db.farcaster_account().fid().find(fid)
+            .ok_or(ErrorCode::FarcasterAccountUnknown)?;
+        if signer != account.custody_address {
+            return Err(ErrorCode::NotCustodyAddress);
+        }
+
+        // Nonces are single use
+        ctx.db.wallet_nonce().identity().delete(ctx.sender);
+
+        store_farcaster_link(ctx, FarcasterLink {
+            identity: ctx.sender,
+            fid,
+            username: account.username,
+            pfp_url: account.pfp_url,
+            custody_address: Some(signer),
+            verified_by: "signature".to_string(),
+            linked_at: ctx.timestamp,
+        })?;
+
+        audit(ctx, "link_farcaster", None, serde_json::json!({ "fid": fid }), "ok");
+        log::info!("Farcaster FID {} linked to {:?}", fid, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Record an FID's custody address and profile as read from the IdRegistry
+/// and hubs (oracle only). Refreshes the profile on any link to the FID, and
+/// drops a signature link made by a custody address the FID has moved away from.
+#[spacetimedb::reducer]
+pub fn set_farcaster_account(
+    ctx: &ReducerContext,
+    fid: u64,
+    custody_address: String,
+    username: String,
+    pfp_url: Option<String>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_farcaster_account", move || {
+        if !is_oracle(ctx, ctx.sender) {
+            return Err(ErrorCode::OracleOnly);
+        }
+
+        let custody_address = normalize_address(&custody_address)?;
+        let username = username.trim().to_string();
+        if username.is_empty() || username.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(ErrorCode::InvalidFarcasterUsername);
+        }
+        if pfp_url.as_ref().is_some_and(|url| url.len() > MAX_AVATAR_URL_LENGTH) {
+            return Err(ErrorCode::UrlTooLong);
+        }
+
+        let account = FarcasterAccount {
+            fid,
+            custody_address: custody_address.clone(),
+            username: username.clone(),
+            pfp_url: pfp_url.clone(),
+            updated_at: ctx.timestamp,
+        };
+        if ctx.db.farcaster_account().fid().find(fid).is_some() {
+            ctx.db.farcaster_account().fid().update(account);
+        } else {
+            ctx.db.farcaster_account().insert(account);
+        }
+
+        if let Some(link) = ctx.db.farcaster_link().fid().find(fid) {
+            let stale = link.verified_by == "signature" && link.custody_address.as_ref() != Some(&custody_address);
+            if stale {
+                ctx.db.farcaster_link().fid().delete(fid);
+            } else {
+                ctx.db.farcaster_link().fid().update(FarcasterLink {
+                    username,
+                    pfp_url,
+                    ..link
+                });
+            }
+        }
+
+        audit(ctx, "set_farcaster_account", None, serde_json::json!({ "fid": fid, "custody_address": custody_address }), "ok");
+        log::info!("Farcaster FID {} custody set to {}", fid, custody_address);
+
+        Ok(())
+    })
+}
+
+/// Link a Farcaster account on a player's behalf after the oracle has
+/// verified it off-chain, e.g. from a miniapp session (oracle only)
+#[spacetimedb::reducer]
+pub fn attest_farcaster(
+    ctx: &ReducerContext,
+    identity: Identity,
+    fid: u64,
+    username: String,
+    pfp_url: Option<String>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "attest_farcaster", move || {
+        if !is_oracle(ctx, ctx.sender) {
+            return Err(ErrorCode::OracleOnly);
+        }
+
+        store_farcaster_link(ctx, FarcasterLink {
+            identity,
+            fid,
+            username: username.trim().to_string(),
+            pfp_url,
+            custody_address: None,
+            verified_by: "oracle".to_string(),
+            linked_at: ctx.timestamp,
+        })?;
+
+        audit(ctx, "attest_farcaster", None, serde_json::json!({ "identity": identity.to_hex().to_string(), "fid": fid }), "ok");
+        log::info!("Farcaster FID {} attested for {:?}", fid, identity);
+
+        Ok(())
+    })
+}
+
+/// Remove the caller's Farcaster link
+#[spacetimedb::reducer]
+pub fn unlink_farcaster(ctx: &ReducerContext) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "unlink_farcaster", move || {
+        if !ctx.db.farcaster_link().identity().delete(ctx.sender) {
+            return Err(ErrorCode::FarcasterNotLinked);
+        }
+
+        audit(ctx, "unlink_farcaster", None, serde_json::json!({}), "ok");
+        log::info!("Farcaster account unlinked from {:?}", ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Create or update the caller's profile
+#[spacetimedb::reducer]
+pub fn set_profile(
+    ctx: &ReducerContext,
+    primary_address: String,
+    display_name: String,
+    avatar_url: Option<String>,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_profile", move || {
+        let address = normalize_address(&primary_address)?;
+        if !is_wallet_linked(ctx, &address, ctx.sender) {
+            return Err(ErrorCode::WalletNotLinked);
+        }
+
+        let display_name = display_name.trim().to_string();
+        if display_name.is_empty() || display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(ErrorCode::InvalidDisplayName);
+        }
+
+        if let Some(ref url) = avatar_url {
+            if url.len() > MAX_AVATAR_URL_LENGTH {
+                return Err(ErrorCode::UrlTooLong);
+            }
+        }
+
+        let payload = serde_json::json!({ "primary_address": address, "display_name": display_name });
+        match ctx.db.profile().identity().find(ctx.sender) {
+            Some(profile) => {
+                ctx.db.profile().identity().update(Profile {
+                    primary_address: address,
+                    display_name,
+                    avatar_url,
+                    updated_at: ctx.timestamp,
+                    ..profile
+                });
+            }
+            None => {
+                ctx.db.profile().insert(Profile {
+                    identity: ctx.sender,
+                    primary_address: address,
+                    display_name,
+                    avatar_url,
+                    preferred_color: None,
+                    created_at: ctx.timestamp,
+                    updated_at: ctx.timestamp,
+                });
+            }
+        }
+
+        audit(ctx, "set_profile", None, payload, "ok");
+        log::info!("Profile updated for {:?}", ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Set (or clear) the colour to use when joining lobbies
+#[spacetimedb::reducer]
+pub fn set_preferred_color(ctx: &ReducerContext, color: Option<String>) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_preferred_color", move || {
+        if let Some(ref color) = color {
+            if !PLAYER_COLORS.contains(&color.as_str()) {
+                return Err(ErrorCode::UnknownColour);
+            }
+        }
+
+        let profile = ctx.db.profile().identity().find(ctx.sender)
+            .ok_or(ErrorCode::ProfileNotFound)?;
+        ctx.db.profile().identity().update(Profile {
+            preferred_color: color.clone(),
+            updated_at: ctx.timestamp,
+            ..profile
+        });
+
+        audit(ctx, "set_preferred_color", None, serde_json::json!({ "color": color }), "ok");
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// MATCHMAKING REDUCERS
+// ============================================================================
+
+/// Join the matchmaking queue (replaces any previous ticket)
+#[spacetimedb::reducer]
+pub fn enter_queue(
+    ctx: &ReducerContext,
+    chain_id: u32,
+    arena_address: String,
+    entry_fee: String,
+    max_players: u32,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "enter_queue", move || {
+        if !(2..=5).contains(&max_players) {
+            return Err(ErrorCode::InvalidPlayerCount);
+        }
+        let entry_fee = parse_entry_fee(&entry_fee)?;
+        let profile = require_profile(ctx)?;
+
+        if let Some(existing) = ctx.db.matchmaking_ticket().identity().find(ctx.sender) {
+            if existing.status == "searching" {
+                return Err(ErrorCode::AlreadyQueued);
+            }
+            ctx.db.matchmaking_ticket().identity().delete(ctx.sender);
+        }
+
+        // Matched lobbies use the standard time control
+        let rating = current_rating(
+            ctx,
+            &player_key(ctx.sender, &profile.primary_address),
+            max_players,
+            variant_name(TimeControl::Standard),
+        );
+
+        ctx.db.matchmaking_ticket().insert(MatchmakingTicket {
+            identity: ctx.sender,
+            address: profile.primary_address,
+            name: profile.display_name,
+            chain_id,
+            arena_address: arena_address.to_lowercase(),
+            entry_fee,
+            max_players,
+            rating,
+            status: "searching".to_string(),
+            lobby_id: None,
+            created_at: ctx.timestamp,
+        });
+
+        ensure_matchmaker_scheduled(ctx);
+
+        audit(ctx, "enter_queue", None, serde_json::json!({ "chain_id": chain_id, "max_players": max_players }), "ok");
+        log::info!("Player {:?} entered the queue", ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Leave the matchmaking queue (or dismiss a matched ticket)
+#[spacetimedb::reducer]
+pub fn leave_queue(ctx: &ReducerContext) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "leave_queue", move || {
+        if !ctx.db.matchmaking_ticket().identity().delete(ctx.sender) {
+            return Err(ErrorCode::NotQueued);
+        }
+
+        audit(ctx, "leave_queue", None, serde_json::json!({}), "ok");
+        log::info!("Player {:?} left the queue", ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Scheduled: group compatible tickets and open a lobby for each full group
+#[spacetimedb::reducer]
+pub fn run_matchmaker(ctx: &ReducerContext, _schedule: MatchmakingSchedule) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "run_matchmaker", move || {
+        if ctx.sender != ctx.identity() {
+            return Err(ErrorCode::SchedulerOnly);
+        }
+
+        // Bucket searching tickets by everything that must match exactly
+        let mut pools: std::collections::BTreeMap<(u32, String, u256, u32), Vec<MatchmakingTicket>> =
+            std::collections::BTreeMap::new();
+        for ticket in ctx.db.matchmaking_ticket().iter().filter(|t| t.status == "searching") {
+            // Tickets hold the lowercased arena and the parsed fee, so equal
+            // values group together however the client spelled them
+            let key = (
+                ticket.chain_id,
+                ticket.arena_address.clone(),
+                ticket.entry_fee,
+                ticket.max_players,
+            );
+            pools.entry(key).or_default().push(ticket);
+        }
+
+        for (_, mut pool) in pools {
+            // Longest-waiting players get matched first
+            pool.sort_by_key(|t| t.created_at);
+
+            while let Some(anchor) = pool.first().cloned() {
+                let size = anchor.max_players as usize;
+                if pool.len() < size {
+                    break;
+                }
+
+                // Everyone in the group must be inside each other's rating band
+                let mut group = vec![anchor.clone()];
+                for candidate in pool.iter().skip(1) {
+                    let fits = group.iter().all(|member| {
+                        let gap = (member.rating - candidate.rating).abs();
+                        gap <= rating_band(ctx.timestamp.duration_since(member.created_at).unwrap_or_default())
+                            && gap <= rating_band(ctx.timestamp.duration_since(candidate.created_at).unwrap_or_default())
+                    });
+                    if fits {
+                        group.push(candidate.clone());
+                        if group.len() == size {
+                            break;
+                        }
+                    }
+                }
+
+                if group.len() < size {
+                    // The oldest ticket can't be matched yet; let the others try without it
+                    pool.remove(0);
+                    continue;
+                }
+
+                let lobby_id = open_matched_lobby(ctx, &group)?;
+                let players: Vec<_> = group.iter().map(|t| t.identity.to_hex().to_string()).collect();
+                audit(ctx, "run_matchmaker", Some(&lobby_id), serde_json::json!({ "players": players }), "matched");
+                pool.retain(|t| !group.iter().any(|g| g.identity == t.identity));
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Open a locked lobby for a matched group and point each ticket at it.
+/// The longest-waiting player hosts and creates the on-chain match.
+fn open_matched_lobby(ctx: &ReducerContext, group: &[MatchmakingTicket]) -> Result<String, ErrorCode> {
+    let host = &group[0];
+
+    let lobby = open_lobby(ctx, LobbySettings {
+        chain_id: host.chain_id,
+        match_id: 0, // Set by the host once the on-chain match exists
+        arena_address: host.arena_address.clone(),
+        entry_fee: host.entry_fee,
+        max_players: host.max_players,
+        time_control: TimeControl::Standard,
+        is_locked: true, // Seats belong to the matched players
+        visibility: LobbyVisibility::Unlisted,
+        allow_spectators: true,
+        max_spectators: None,
+        previous_lobby_id: None,
+        tournament_id: None,
+    }, group.iter().map(|ticket| Seat {
+        identity: ticket.identity,
+        address: ticket.address.clone(),
+        name: ticket.name.clone(),
+        color: None,
+        has_deposited: false, // Everyone deposits through confirm_deposit
+        joined_at: ticket.created_at, // Queue order decides turn order
+    }).collect(), "matchmaking")?;
+    let lobby_id = lobby.id;
+
+    for ticket in group {
+        ctx.db.matchmaking_ticket().identity().update(MatchmakingTicket {
+            status: "matched".to_string(),
+            lobby_id: Some(lobby_id.clone()),
+            ..ticket.clone()
+        });
+    }
+
+    // Cancel the lobby if the match id and deposits don't arrive in time
+    ctx.db.deposit_deadline().insert(DepositDeadline {
+        scheduled_id: 0,
+        scheduled_at: (ctx.timestamp + std::time::Duration::from_secs(MATCHED_DEPOSIT_WINDOW_SECS)).into(),
+        lobby_id: lobby_id.clone(),
+    });
+
+    log::info!("Matchmaker opened lobby {} for {} players", lobby_id, group.len());
+
+    Ok(lobby_id)
+}
+
+/// Scheduled: cancel a matched lobby that hasn't started by its deposit
+/// deadline and refund whoever already paid
+#[spacetimedb::reducer]
+pub fn expire_matched_lobby(ctx: &ReducerContext, deadline: DepositDeadline) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "expire_matched_lobby", move || {
+        if ctx.sender != ctx.identity() {
+            return Err(ErrorCode::SchedulerOnly);
+        }
+
+        let Some(lobby) = ctx.db.lobby().id().find(&deadline.lobby_id) else {
+            return Ok(());
+        };
+        if lobby.status != LobbyStatus::Waiting {
+            return Ok(());
+        }
+
+        for player in ctx.db.lobby_player().lobby_id().filter(&lobby.id) {
+            refund_deposit(ctx, &lobby, &player, "deposit_timeout");
+        }
+        transition_lobby(ctx, lobby, LobbyStatus::Cancelled, "deposit_timeout")?;
+
+        audit(ctx, "expire_matched_lobby", Some(&deadline.lobby_id), serde_json::json!({}), "cancelled");
+        log::info!("Matched lobby {} cancelled, deposits not made in time", deadline.lobby_id);
+
+        Ok(())
+    })
+}
+
+/// Attach the on-chain match to a matchmade lobby (host only, once)
+#[spacetimedb::reducer]
+pub fn set_match_id(ctx: &ReducerContext, lobby_id: String, match_id: u64) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "set_match_id", move || {
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.host_identity != ctx.sender {
+            return Err(ErrorCode::HostOnly);
+        }
+        if lobby.status != LobbyStatus::Waiting {
+            return Err(ErrorCode::GameAlreadyStarted);
+        }
+        if lobby.match_id != 0 {
+            return Err(ErrorCode::MatchIdAlreadySet);
+        }
+
+        ctx.db.lobby().id().update(Lobby {
+            match_id,
+            updated_at: ctx.timestamp,
+            ..lobby
+        });
+
+        audit(ctx, "set_match_id", Some(&lobby_id), serde_json::json!({ "match_id": match_id }), "ok");
+        log::info!("Lobby {} bound to on-chain match {}", lobby_id, match_id);
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// SETTLEMENT REDUCERS
+// ============================================================================
+
+/// Record that the arena contract has finished a lobby's match, moving the
+/// lobby on to settling (oracle only)
+#[spacetimedb::reducer]
+pub fn record_match_finished(ctx: &ReducerContext, lobby_id: String, tx_hash: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "record_match_finished", move || {
+        if !is_oracle(ctx, ctx.sender) {
+            return Err(ErrorCode::OracleOnly);
+        }
+
+        let lobby = ctx.db.lobby().id().find(&lobby_id)
+            .ok_or(ErrorCode::LobbyNotFound)?;
+
+        if lobby.status != LobbyStatus::Finished {
+            return Err(ErrorCode::MatchNotAwaitingOracle);
+        }
+
+        transition_lobby(ctx, lobby, LobbyStatus::Settling, "match_finished_onchain")?;
+
+        audit(ctx, "record_match_finished", Some(&lobby_id), serde_json::json!({ "tx_hash": tx_hash }), "ok");
+        log::info!("Lobby {} finished on-chain in {}", lobby_id, tx_hash);
+
+        Ok(())
+    })
+}
+
+/// Record that a settlement was paid on-chain (oracle only)
+#[spacetimedb::reducer]
+pub fn mark_settlement_executed(ctx: &ReducerContext, settlement_id: u64, tx_hash: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "mark_settlement_executed", move || {
+        if !is_oracle(ctx, ctx.sender) {
+            return Err(ErrorCode::OracleOnly);
+        }
+
+        let settlement = ctx.db.settlement().id().find(settlement_id)
+            .ok_or(ErrorCode::SettlementNotFound)?;
+
+        if settlement.status != "pending" {
+            return Err(ErrorCode::SettlementAlreadyExecuted);
+        }
+
+        let lobby_id = settlement.lobby_id.clone();
+        ctx.db.settlement().id().update(Settlement {
+            status: "executed".to_string(),
+            tx_hash: Some(tx_hash.clone()),
+            executed_at: Some(ctx.timestamp),
+            ..settlement
+        });
+        update_payout_status(ctx, &lobby_id)?;
+
+        audit(ctx, "mark_settlement_executed", None, serde_json::json!({ "settlement_id": settlement_id, "tx_hash": tx_hash }), "ok");
+        log::info!("Settlement {} executed", settlement_id);
+
+        Ok(())
+    })
+}
+
+/// Mark a refund as paid back on-chain (oracle only)
+#[spacetimedb::reducer]
+pub fn mark_refund_processed(ctx: &ReducerContext, refund_id: u64, tx_hash: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "mark_refund_processed", move || {
+        if !is_oracle(ctx, ctx.sender) {
+            return Err(ErrorCode::OracleOnly);
+        }
+
+        let refund = ctx.db.refund().id().find(refund_id)
+            .ok_or(ErrorCode::RefundNotFound)?;
+
+        if refund.processed {
+            return Err(ErrorCode::RefundAlreadyProcessed);
+        }
+
+        let lobby_id = refund.lobby_id.clone();
+        ctx.db.refund().id().update(Refund {
+            processed: true,
+            tx_hash: Some(tx_hash.clone()),
+            processed_at: Some(ctx.timestamp),
+            ..refund
+        });
+
+        audit(ctx, "mark_refund_processed", Some(&lobby_id), serde_json::json!({ "refund_id": refund_id, "tx_hash": tx_hash }), "ok");
+        log::info!("Refund {} processed", refund_id);
+
+        Ok(())
+    })
+}
+
+/// Allow an identity to act as the payout oracle (moderators only)
+#[spacetimedb::reducer]
+pub fn register_oracle(ctx: &ReducerContext, identity: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "register_oracle", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+        if is_oracle(ctx, identity) {
+            return Err(ErrorCode::AlreadyOracle);
+        }
+
+        ctx.db.oracle().insert(Oracle {
+            identity,
+            added_by: ctx.sender,
+            added_at: ctx.timestamp,
+        });
+
+        audit(ctx, "register_oracle", None, serde_json::json!({ "identity": identity.to_hex().to_string() }), "ok");
+        log::info!("Oracle {:?} registered by {:?}", identity, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Revoke an oracle identity (moderators only)
+#[spacetimedb::reducer]
+pub fn remove_oracle(ctx: &ReducerContext, identity: Identity) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "remove_oracle", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+
+        if !ctx.db.oracle().identity().delete(identity) {
+            return Err(ErrorCode::NotAnOracle);
+        }
+
+        audit(ctx, "remove_oracle", None, serde_json::json!({ "identity": identity.to_hex().to_string() }), "ok");
+        log::info!("Oracle {:?} removed by {:?}", identity, ctx.sender);
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// SEASON REDUCERS
+// ============================================================================
+
+/// End the active season, archive its final leaderboards and start a new one
+/// (moderators only)
+#[spacetimedb::reducer]
+pub fn start_new_season(ctx: &ReducerContext, name: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "start_new_season", move || {
+        if !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::ModeratorOnly);
+        }
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(ErrorCode::InvalidSeasonName);
+        }
+
+        let season_id = current_season_id(ctx);
+
+        // Group the season's entries by board and rank each category
+        let mut boards: std::collections::BTreeMap<(u32, u32, String), Vec<LeaderboardEntry>> =
+            std::collections::BTreeMap::new();
+        for entry in ctx.db.leaderboard_entry().iter().filter(|e| e.season_id == season_id) {
+            boards.entry((entry.chain_id, entry.max_players, entry.variant.clone()))
+                .or_default()
+                .push(entry);
+        }
+
+        for (_, entries) in boards {
+            let wins: Vec<u32> = entries.iter().map(|e| e.wins).collect();
+            let earnings: Vec<u256> = entries.iter().map(|e| e.earnings).collect();
+            let ratings: Vec<i32> = entries.iter().map(|e| e.rating).collect();
+            let wins_ranks = rank_descending(&wins);
+            let earnings_ranks = rank_descending(&earnings);
+            let rating_ranks = rank_descending(&ratings);
+
+            for (i, entry) in entries.into_iter().enumerate() {
+                ctx.db.season_archive().insert(SeasonArchive {
+                    id: 0,
+                    season_id,
+                    chain_id: entry.chain_id,
+                    max_players: entry.max_players,
+                    variant: entry.variant,
+                    player_key: entry.player_key,
+                    identity: entry.identity,
+                    name: entry.name,
+                    games: entry.games,
+                    wins: entry.wins,
+                    earnings: entry.earnings,
+                    rating: entry.rating,
+                    wins_rank: wins_ranks[i],
+                    earnings_rank: earnings_ranks[i],
+                    rating_rank: rating_ranks[i],
+                });
+            }
+        }
+
+        let season = ctx.db.season().id().find(season_id)
+            .expect("current_season_id returns an existing season");
+        ctx.db.season().id().update(Season {
+            is_active: false,
+            ended_at: Some(ctx.timestamp),
+            ..season
+        });
+
+        let new_season = ctx.db.season().insert(Season {
+            id: 0,
+            name,
+            is_active: true,
+            started_at: ctx.timestamp,
+            ended_at: None,
+        });
+
+        audit(ctx, "start_new_season", None, serde_json::json!({ "archived_season_id": season_id, "season_id": new_season.id }), "ok");
+        log::info!("Season {} archived, season {} started", season_id, new_season.id);
+
+        Ok(())
+    })
+}
+
+// ============================================================================
+// TOURNAMENT REDUCERS
+// ============================================================================
+
+/// Create a tournament open for registration. A knockout with two players
+/// per match plays a single-elimination bracket; three to five plays heats
+/// where only the heat winner advances. Swiss tournaments are head to head.
+#[spacetimedb::reducer]
+#[allow(clippy::too_many_arguments)]
+pub fn create_tournament(
+    ctx: &ReducerContext,
+    name: String,
+    chain_id: u32,
+    arena_address: String,
+    entry_fee: String,
+    format: TournamentFormat,
+    players_per_match: u32,
+    time_control: TimeControl,
+    max_entries: u32,
+    round_deadline_minutes: u32,
+) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "create_tournament", move || {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(ErrorCode::InvalidTournamentName);
+        }
+        if !(2..=5).contains(&players_per_match) {
+            return Err(ErrorCode::InvalidPlayerCount);
+        }
+        if let TournamentFormat::Swiss(settings) = format {
+            if players_per_match != 2 {
+                return Err(ErrorCode::SwissHeadToHead);
+            }
+            if !(1..=MAX_SWISS_ROUNDS).contains(&settings.rounds) {
+                return Err(ErrorCode::InvalidSwissRounds);
+            }
+        }
+        if !(MIN_TOURNAMENT_ENTRIES..=MAX_TOURNAMENT_ENTRIES).contains(&max_entries) {
+            return Err(ErrorCode::InvalidEntryLimit);
+        }
+        if !(1..=MAX_ROUND_DEADLINE_MINUTES).contains(&round_deadline_minutes) {
+            return Err(ErrorCode::InvalidRoundDeadline);
+        }
+        validate_time_control(time_control)?;
+        let entry_fee = parse_entry_fee(&entry_fee)?;
+
+        let id = generate_id(ctx);
+        ctx.db.tournament().insert(Tournament {
+            id: id.clone(),
+            name,
+            organizer: ctx.sender,
+            chain_id,
+            arena_address,
+            entry_fee,
+            format,
+            players_per_match,
+            time_control,
+            max_entries,
+            status: "registration".to_string(),
+            current_round: 0,
+            round_deadline_minutes,
+            round_deadline: None,
+            winner_identity: None,
+            created_at: ctx.timestamp,
+            updated_at: ctx.timestamp,
+        });
+
+        audit(ctx, "create_tournament", None, serde_json::json!({ "tournament_id": id, "players_per_match": players_per_match, "max_entries": max_entries }), "ok");
+        log::info!("Tournament {} created by {:?}", id, ctx.sender);
+
+        Ok(())
+    })
+}
+
+/// Register for a tournament that has not started yet
+#[spacetimedb::reducer]
+pub fn register_for_tournament(ctx: &ReducerContext, tournament_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "register_for_tournament", move || {
+        let tournament = ctx.db.tournament().id().find(&tournament_id)
+            .ok_or(ErrorCode::TournamentNotFound)?;
+
+        if tournament.status != "registration" {
+            return Err(ErrorCode::RegistrationClosed);
+        }
+
+        let entry_id = format!("{}_{:?}", tournament_id, ctx.sender);
+        if ctx.db.tournament_entry().id().find(&entry_id).is_some() {
+            return Err(ErrorCode::AlreadyRegistered);
+        }
+        let entries = ctx.db.tournament_entry().tournament_id().filter(&tournament_id).count() as u32;
+        if entries >= tournament.max_entries {
+            return Err(ErrorCode::TournamentFull);
+        }
+
+        let profile = require_profile(ctx)?;
+        let rating = current_rating(
+            ctx,
+            &player_key(ctx.sender, &profile.primary_address),
+            tournament.players_per_match,
+            variant_name(tournament.time_control),
+        );
+
+        ctx.db.tournament_entry().insert(TournamentEntry {
+            id: entry_id,
+            tournament_id: tournament_id.clone(),
+            identity: ctx.sender,
+            address: profile.primary_address,
+            name: profile.display_name,
+            rating,
+            seed: 0,
+            status: "active".to_string(),
+            eliminated_in_round: None,
+            score: 0,
+            buchholz: 0,
+            sonneborn_berger: 0,
+            rank: 0,
+            joined_at: ctx.timestamp,
+        });
+
+        audit(ctx, "register_for_tournament", None, serde_json::json!({ "tournament_id": tournament_id }), "ok");
+        log::info!("Player {:?} registered for tournament {}", ctx.sender, tournament_id);
+
+        Ok(())
+    })
+}
+
+/// Withdraw from a tournament before it starts
+#[spacetimedb::reducer]
+pub fn withdraw_from_tournament(ctx: &ReducerContext, tournament_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "withdraw_from_tournament", move || {
+        let tournament = ctx.db.tournament().id().find(&tournament_id)
+            .ok_or(ErrorCode::TournamentNotFound)?;
+
+        if tournament.status != "registration" {
+            return Err(ErrorCode::TournamentAlreadyStarted);
+        }
+
+        let entry_id = format!("{}_{:?}", tournament_id, ctx.sender);
+        if !ctx.db.tournament_entry().id().delete(&entry_id) {
+            return Err(ErrorCode::NotRegistered);
+        }
+
+        audit(ctx, "withdraw_from_tournament", None, serde_json::json!({ "tournament_id": tournament_id }), "ok");
+        log::info!("Player {:?} withdrew from tournament {}", ctx.sender, tournament_id);
 
-    log::info!("Lobby created: {} by {:?}", lobby_id, ctx.sender);
+        Ok(())
+    })
 }
 
-/// Join an existing lobby
+/// Close registration, seed the entries by rating and open round one
+/// (organizer only)
 #[spacetimedb::reducer]
-pub fn join_lobby(
-    ctx: &ReducerContext,
-    lobby_id: String,
-    player_address: String,
-    player_name: String,
-) {
-    // Get lobby
-    let lobby = ctx.db.lobby().id().find(&lobby_id)
-        .expect("Lobby not found");
+pub fn start_tournament(ctx: &ReducerContext, tournament_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "start_tournament", move || {
+        let tournament = ctx.db.tournament().id().find(&tournament_id)
+            .ok_or(ErrorCode::TournamentNotFound)?;
 
-    if lobby.status != "waiting" {
-        panic!("Lobby is not accepting players");
-    }
-
-    // Check if already joined
-    let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
-    if ctx.db.lobby_player().id().find(&player_id).is_some() {
-        panic!("Already joined this lobby");
-    }
+        if tournament.organizer != ctx.sender {
+            return Err(ErrorCode::OrganizerOnly);
+        }
+        if tournament.status != "registration" {
+            return Err(ErrorCode::TournamentAlreadyStarted);
+        }
 
-    // Get current players
-    let players: Vec<_> = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .collect();
+        // Highest rating is seed 1; earlier registration breaks ties
+        let mut entries: Vec<_> = ctx.db.tournament_entry().tournament_id().filter(&tournament_id).collect();
+        if (entries.len() as u32) < MIN_TOURNAMENT_ENTRIES {
+            return Err(ErrorCode::NotEnoughEntries);
+        }
+        entries.sort_by_key(|e| (std::cmp::Reverse(e.rating), e.joined_at));
 
-    if players.len() >= lobby.max_players as usize {
-        panic!("Lobby is full");
-    }
+        let seeded: Vec<_> = entries.into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                ctx.db.tournament_entry().id().update(TournamentEntry {
+                    seed: i as u32 + 1,
+                    ..entry
+                })
+            })
+            .collect();
 
-    // Assign color
-    let used_colors: std::collections::HashSet<String> = players.iter().map(|p| p.color.clone()).collect();
-    let available_color = PLAYER_COLORS.iter()
-        .find(|c| !used_colors.contains(&c.to_string()))
-        .unwrap_or(&PLAYER_COLORS[0]);
+        let groups = if let TournamentFormat::Swiss(_) = tournament.format {
+            // Round one: top half meets bottom half; an odd field gives the last seed the bye
+            let half = seeded.len() / 2;
+            let mut groups: Vec<Vec<TournamentEntry>> = (0..half)
+                .map(|i| vec![seeded[i].clone(), seeded[i + half].clone()])
+                .collect();
+            if seeded.len() % 2 == 1 {
+                groups.push(vec![seeded[seeded.len() - 1].clone()]);
+            }
+            groups
+        } else if tournament.players_per_match == 2 {
+            // Top seeds get the byes when the field is not a power of two
+            let positions = bracket_positions((seeded.len() as u32).next_power_of_two());
+            positions.chunks(2)
+                .map(|pair| {
+                    let mut seeds: Vec<u32> = pair.iter().copied().filter(|&s| s as usize <= seeded.len()).collect();
+                    seeds.sort();
+                    seeds.into_iter().map(|s| seeded[s as usize - 1].clone()).collect()
+                })
+                .collect()
+        } else {
+            snake_heats(&seeded, tournament.players_per_match as usize)
+        };
 
-    // Add player
-    ctx.db.lobby_player().insert(LobbyPlayer {
-        id: player_id,
-        lobby_id: lobby_id.clone(),
-        identity: ctx.sender,
-        address: player_address,
-        name: player_name,
-        color: available_color.to_string(),
-        is_host: false,
-        is_alive: true,
-        has_deposited: false,
-        joined_at: ctx.timestamp,
-    });
+        let tournament = ctx.db.tournament().id().update(Tournament {
+            status: "running".to_string(),
+            updated_at: ctx.timestamp,
+            ..tournament
+        });
+        open_tournament_round(ctx, tournament, groups)?;
 
-    // Update lobby timestamp
-    ctx.db.lobby().id().update(Lobby {
-        updated_at: ctx.timestamp,
-        ..lobby
-    });
+        audit(ctx, "start_tournament", None, serde_json::json!({ "tournament_id": tournament_id, "entries": seeded.len() }), "ok");
+        log::info!("Tournament {} started with {} entries", tournament_id, seeded.len());
 
-    log::info!("Player {:?} joined lobby {}", ctx.sender, lobby_id);
+        Ok(())
+    })
 }
 
-/// Mark player as having deposited USDC on-chain
+/// Cancel a tournament that has not started yet (organizer or moderators)
 #[spacetimedb::reducer]
-pub fn confirm_deposit(
-    ctx: &ReducerContext,
-    lobby_id: String,
-    player_address: String,
-) {
-    // Find player by address in this lobby
-    let player = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .find(|p| p.address.to_lowercase() == player_address.to_lowercase())
-        .expect("Player not found in lobby");
+pub fn cancel_tournament(ctx: &ReducerContext, tournament_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "cancel_tournament", move || {
+        let tournament = ctx.db.tournament().id().find(&tournament_id)
+            .ok_or(ErrorCode::TournamentNotFound)?;
 
-    ctx.db.lobby_player().id().update(LobbyPlayer {
-        has_deposited: true,
-        ..player
-    });
+        if tournament.organizer != ctx.sender && !is_moderator(ctx, ctx.sender) {
+            return Err(ErrorCode::OrganizerOnly);
+        }
+        if tournament.status != "registration" {
+            return Err(ErrorCode::TournamentAlreadyStarted);
+        }
+
+        ctx.db.tournament().id().update(Tournament {
+            status: "cancelled".to_string(),
+            updated_at: ctx.timestamp,
+            ..tournament
+        });
+
+        audit(ctx, "cancel_tournament", None, serde_json::json!({ "tournament_id": tournament_id }), "cancelled");
+        log::info!("Tournament {} cancelled", tournament_id);
 
-    log::info!("Deposit confirmed for {} in lobby {}", player_address, lobby_id);
+        Ok(())
+    })
 }
 
-/// Start the game (host only)
+/// Forfeit the round's unplayed matches once its deadline has passed.
+/// In each lobby that never started, the best seed who deposited and readied
+/// up takes the win; if nobody did, nobody does. Matches already live play
+/// on under their own turn timeouts.
 #[spacetimedb::reducer]
-pub fn start_game(ctx: &ReducerContext, lobby_id: String) {
-    let lobby = ctx.db.lobby().id().find(&lobby_id)
-        .expect("Lobby not found");
+pub fn claim_round_timeout(ctx: &ReducerContext, tournament_id: String) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "claim_round_timeout", move || {
+        let tournament = ctx.db.tournament().id().find(&tournament_id)
+            .ok_or(ErrorCode::TournamentNotFound)?;
 
-    if lobby.host_identity != ctx.sender {
-        panic!("Only host can start the game");
-    }
-    if lobby.status != "waiting" {
-        panic!("Game already started");
-    }
+        if tournament.status != "running" {
+            return Err(ErrorCode::TournamentNotRunning);
+        }
+        if tournament.round_deadline.is_some_and(|deadline| ctx.timestamp < deadline) {
+            return Err(ErrorCode::RoundNotTimedOut);
+        }
 
-    let players: Vec<_> = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .collect();
+        let unplayed: Vec<_> = ctx.db.tournament_match()
+            .tournament_id()
+            .filter(&tournament_id)
+            .filter(|m| m.round == tournament.current_round && m.status == "live")
+            .filter_map(|m| {
+                let lobby = ctx.db.lobby().id().find(m.lobby_id.clone()?)?;
+                (lobby.status == LobbyStatus::Waiting).then_some((m, lobby))
+            })
+            .collect();
+        if unplayed.is_empty() {
+            return Err(ErrorCode::RoundInProgress);
+        }
 
-    if players.len() < 2 {
-        panic!("Need at least 2 players to start");
-    }
+        for (tournament_match, lobby) in unplayed {
+            let players: Vec<_> = ctx.db.lobby_player().lobby_id().filter(&lobby.id).collect();
 
-    // Check all players have deposited
-    if !players.iter().all(|p| p.has_deposited) {
-        panic!("Not all players have deposited USDC");
-    }
+            // Players are listed best seed first
+            let winner = tournament_match.players.iter().copied().find(|identity| {
+                players.iter().any(|p| p.identity == *identity && p.has_deposited && p.is_ready)
+            });
 
-    // Update lobby status
-    ctx.db.lobby().id().update(Lobby {
-        status: "live".to_string(),
-        updated_at: ctx.timestamp,
-        ..lobby
-    });
+            // The match never happened, so every deposit goes back
+            for player in &players {
+                refund_deposit(ctx, &lobby, player, "round_timeout");
+            }
 
-    // Set initial turn deadline (30 seconds)
-    // 30 seconds = 30 * 1,000,000 microseconds
-    let deadline = ctx.timestamp + std::time::Duration::from_secs(30);
-    
-    let game_state = ctx.db.game_state().lobby_id().find(&lobby_id).expect("Game state not found");
-    ctx.db.game_state().lobby_id().update(GameState {
-        turn_deadline: deadline,
-        ..game_state
-    });
+            transition_lobby(ctx, lobby, LobbyStatus::Cancelled, "round_timeout")?;
+            settle_tournament_match(ctx, tournament_match, winner, "forfeit");
+        }
+
+        advance_tournament(ctx, &tournament_id)?;
 
-    log::info!("Game started in lobby {}", lobby_id);
+        audit(ctx, "claim_round_timeout", None, serde_json::json!({ "tournament_id": tournament_id, "round": tournament.current_round }), "ok");
+        log::info!("Round {} of tournament {} timed out", tournament.current_round, tournament_id);
+
+        Ok(())
+    })
 }
 
-/// Make a move (place orb)
+// ============================================================================
+// AUDIT REDUCERS
+// ============================================================================
+
+/// Scheduled: delete audit events past the retention period
 #[spacetimedb::reducer]
-pub fn make_move(
-    ctx: &ReducerContext,
-    lobby_id: String,
-    row: u32,
-    col: u32,
-) {
-    // Validate lobby
-    let lobby = ctx.db.lobby().id().find(&lobby_id)
-        .expect("Lobby not found");
+pub fn prune_audit_events(ctx: &ReducerContext, _schedule: AuditPruneSchedule) -> Result<(), ErrorCode> {
+    log_rejections(ctx, "prune_audit_events", move || {
+        if ctx.sender != ctx.identity() {
+            return Err(ErrorCode::SchedulerOnly);
+        }
 
-    if lobby.status != "live" {
-        panic!("Game is not live");
-    }
+        let cutoff = ctx.timestamp - std::time::Duration::from_secs(AUDIT_RETENTION_DAYS * 24 * 60 * 60);
+        let pruned = ctx.db.audit_event()
+            .timestamp_micros()
+            .delete(..cutoff.to_micros_since_unix_epoch());
+
+        if pruned > 0 {
+            log::info!("Pruned {} audit events", pruned);
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
 
-    // Get game state
-    let game_state = ctx.db.game_state().lobby_id().find(&lobby_id)
-        .expect("Game state not found");
+    fn player(n: u8) -> Identity {
+        Identity::from_byte_array([n; 32])
+    }
 
-    // === TURN LOCK CHECK (Prevent race conditions) ===
-    if let Some(lock_time) = game_state.turn_lock_until {
-        if ctx.timestamp < lock_time {
-            panic!("Turn is locked, please retry in 50ms");
+    fn entry(n: u8, seed: u32) -> TournamentEntry {
+        TournamentEntry {
+            id: format!("t_{}", n),
+            tournament_id: "t".to_string(),
+            identity: player(n),
+            address: String::new(),
+            name: format!("player {}", n),
+            rating: 0,
+            seed,
+            status: "active".to_string(),
+            eliminated_in_round: None,
+            score: 0,
+            buchholz: 0,
+            sonneborn_berger: 0,
+            rank: 0,
+            joined_at: Timestamp::UNIX_EPOCH,
         }
     }
 
-    // === RATE LIMITING (Prevent spam) ===
-    if let Some(time_since_last) = ctx.timestamp.duration_since(game_state.last_move_at) {
-        if time_since_last < std::time::Duration::from_millis(300) {
-            panic!("Move too fast, wait 300ms between moves");
+    fn decided(players: &[u8], winner: Option<u8>, status: &str) -> TournamentMatch {
+        TournamentMatch {
+            id: format!("t_{:?}", players),
+            tournament_id: "t".to_string(),
+            round: 1,
+            slot: 0,
+            lobby_id: None,
+            players: players.iter().map(|&n| player(n)).collect(),
+            winner_identity: winner.map(player),
+            status: status.to_string(),
         }
     }
 
-    // Get alive players sorted by join time
-    let mut players: Vec<_> = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .filter(|p| p.is_alive)
-        .collect();
-    players.sort_by_key(|p| p.joined_at);
+    fn played(pairs: &[(u8, u8)]) -> HashSet<(Identity, Identity)> {
+        pairs.iter()
+            .flat_map(|&(a, b)| [(player(a), player(b)), (player(b), player(a))])
+            .collect()
+    }
 
-    if players.is_empty() {
-        panic!("No active players");
+    fn order(standings: &[TournamentEntry]) -> Vec<Identity> {
+        standings.iter().map(|e| e.identity).collect()
     }
 
-    // === AUTO-TIMEOUT CHECK ===
-    // If current player's turn has expired, auto-eliminate them
-    if ctx.timestamp > game_state.turn_deadline {
-        let timed_out_idx = game_state.current_player_index as usize % players.len();
-        let timed_out_player = &players[timed_out_idx];
-        
-        log::info!("Player {} auto-timed out!", timed_out_player.name);
-        
-        // Eliminate timed-out player
-        ctx.db.lobby_player().id().update(LobbyPlayer {
-            is_alive: false,
-            ..timed_out_player.clone()
-        });
-        
-        // Refresh alive players
-        players = ctx.db.lobby_player()
-            .lobby_id()
-            .filter(&lobby_id)
-            .filter(|p| p.is_alive)
-            .collect();
-        players.sort_by_key(|p| p.joined_at);
-        
-        // Check for winner after timeout
-        if players.len() == 1 {
-            let winner = &players[0];
-            ctx.db.lobby().id().update(Lobby {
-                status: "finished".to_string(),
-                winner_identity: Some(winner.identity),
-                winner_address: Some(winner.address.clone()),
-                updated_at: ctx.timestamp,
-                ..lobby
-            });
-            log::info!("Game finished by auto-timeout! Winner: {}", winner.name);
-            return; // Exit early
-        }
-        
-        if players.is_empty() {
-            panic!("No players left after timeout");
+    #[test]
+    fn error_code_list_covers_every_variant() {
+        use spacetimedb::spacetimedb_lib::db::raw_def::v9::RawModuleDefV9Builder;
+
+        let mut builder = RawModuleDefV9Builder::new();
+        let ty = <ErrorCode as SpacetimeType>::make_type(&mut builder);
+        let variants = ty.as_ref()
+            .and_then(|r| builder.typespace().get(*r))
+            .and_then(|t| t.as_sum())
+            .map(|sum| sum.variants.len());
+        assert_eq!(variants, Some(ErrorCode::ALL.len()));
+
+        let codes: HashSet<_> = ErrorCode::ALL.iter().map(|c| c.code()).collect();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+        assert_eq!(ErrorCode::LobbyFull.message(), "Lobby is full");
+    }
+
+    #[test]
+    fn bracket_positions_keep_top_seeds_apart() {
+        assert_eq!(bracket_positions(1), vec![1]);
+        assert_eq!(bracket_positions(2), vec![1, 2]);
+        assert_eq!(bracket_positions(4), vec![1, 4, 2, 3]);
+        assert_eq!(bracket_positions(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+
+        let positions = bracket_positions(16);
+        for pair in positions.chunks(2) {
+            assert_eq!(pair[0] + pair[1], 17);
         }
+        let (top, bottom) = positions.split_at(8);
+        assert!(top.contains(&1) && bottom.contains(&2));
     }
 
-    // Check if it's this player's turn
-    let current_idx = game_state.current_player_index as usize % players.len();
-    let current_player = &players[current_idx];
-    if current_player.identity != ctx.sender {
-        panic!("Not your turn");
+    #[test]
+    fn next_bracket_round_pairs_neighbouring_slots() {
+        let groups = next_bracket_round(vec![Some('a'), Some('b'), Some('c'), Some('d')]);
+        assert_eq!(groups, vec![vec!['a', 'b'], vec!['c', 'd']]);
     }
 
-    // Parse board
-    let mut board: Board = serde_json::from_str(&game_state.board_json).unwrap();
+    #[test]
+    fn next_bracket_round_gives_bye_when_neighbour_has_no_winner() {
+        // Slot 1 had no winner: 'a' gets a bye and 'c' still meets 'd'
+        let groups = next_bracket_round(vec![Some('a'), None, Some('c'), Some('d')]);
+        assert_eq!(groups, vec![vec!['a'], vec!['c', 'd']]);
 
-    // Get board dimensions from game state
-    let rows = game_state.rows as usize;
-    let cols = game_state.cols as usize;
-    
-    // Validate move
-    let r = row as usize;
-    let c = col as usize;
-    if r >= rows || c >= cols {
-        panic!("Invalid position");
+        let groups = next_bracket_round(vec![None, Some('b'), Some('c'), None]);
+        assert_eq!(groups, vec![vec!['b'], vec!['c']]);
     }
 
-    let cell = &board[r][c];
-    if cell.owner.is_some() && cell.owner.as_ref() != Some(&current_player.color) {
-        panic!("Cell owned by another player");
+    #[test]
+    fn next_bracket_round_keeps_empty_slots() {
+        let groups = next_bracket_round(vec![None, None, Some('c'), Some('d'), Some('e'), Some('f'), None, Some('h')]);
+        assert_eq!(groups, vec![vec![], vec!['c', 'd'], vec!['e', 'f'], vec!['h']]);
+
+        // The next round still lines up slot by slot
+        assert_eq!(next_bracket_round(vec![None, Some('c'), Some('e'), Some('h')]), vec![vec!['c'], vec!['e', 'h']]);
     }
 
-    // Place orb
-    board[r][c].orbs += 1;
-    board[r][c].owner = Some(current_player.color.clone());
+    #[test]
+    fn next_bracket_round_handles_odd_slot_count() {
+        assert_eq!(next_bracket_round(vec![Some('a'), Some('b'), Some('c')]), vec![vec!['a', 'b'], vec!['c']]);
+    }
 
-    // Process chain reactions
-    loop {
-        let mut had_explosion = false;
+    #[test]
+    fn pair_without_rematches_pairs_straight_down_without_history() {
+        let players = [player(1), player(2), player(3), player(4)];
+        let mut budget = SWISS_PAIRING_BUDGET;
+        let pairs = pair_without_rematches(&players, &HashSet::new(), &mut budget);
+        assert_eq!(pairs, Some(vec![(player(1), player(2)), (player(3), player(4))]));
+    }
 
-        for row_idx in 0..rows {
-            for col_idx in 0..cols {
-                let max_cap = get_max_capacity(row_idx, col_idx, rows, cols);
-                if board[row_idx][col_idx].orbs >= max_cap {
-                    had_explosion = true;
+    #[test]
+    fn pair_without_rematches_drops_down_to_avoid_rematch() {
+        let players = [player(1), player(2), player(3), player(4)];
+        let mut budget = SWISS_PAIRING_BUDGET;
+        let pairs = pair_without_rematches(&players, &played(&[(1, 2)]), &mut budget);
+        assert_eq!(pairs, Some(vec![(player(1), player(3)), (player(2), player(4))]));
 
-                    let exploding_owner = board[row_idx][col_idx].owner.clone();
-                    board[row_idx][col_idx].orbs = 0;
-                    board[row_idx][col_idx].owner = None;
+        // Backtracks when the nearest partner leaves the rest unpairable
+        let mut budget = SWISS_PAIRING_BUDGET;
+        let pairs = pair_without_rematches(&players, &played(&[(1, 2), (3, 4), (2, 4)]), &mut budget);
+        assert_eq!(pairs, Some(vec![(player(1), player(4)), (player(2), player(3))]));
+    }
 
-                    // Spread to neighbors
-                    let neighbors: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-                    for (dr, dc) in neighbors {
-                        let nr = row_idx as i32 + dr;
-                        let nc = col_idx as i32 + dc;
-                        if nr >= 0 && nr < rows as i32 && nc >= 0 && nc < cols as i32 {
-                            let nr = nr as usize;
-                            let nc = nc as usize;
-                            board[nr][nc].orbs += 1;
-                            board[nr][nc].owner = exploding_owner.clone();
-                        }
-                    }
-                }
-            }
-        }
+    #[test]
+    fn pair_without_rematches_gives_up_when_impossible_or_out_of_budget() {
+        let mut budget = SWISS_PAIRING_BUDGET;
+        assert_eq!(pair_without_rematches(&[player(1), player(2)], &played(&[(1, 2)]), &mut budget), None);
 
-        if !had_explosion {
-            break;
-        }
+        let mut budget = 0;
+        assert_eq!(pair_without_rematches(&[player(1), player(2)], &HashSet::new(), &mut budget), None);
     }
 
-    // Count orbs per player
-    let mut player_orb_counts: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
-    for player in &players {
-        player_orb_counts.insert(player.color.clone(), 0);
+    #[test]
+    fn swiss_bye_goes_to_lowest_ranked_without_one() {
+        let standings = vec![entry(1, 1), entry(2, 2), entry(3, 3)];
+        assert_eq!(swiss_bye(&standings[..2], &[]), None);
+        assert_eq!(swiss_bye(&standings, &[]), Some(2));
+
+        let previous = [decided(&[3], Some(3), "bye")];
+        assert_eq!(swiss_bye(&standings, &previous), Some(1));
+
+        // Once everyone has sat out, the bottom player does again
+        let previous = [
+            decided(&[1], Some(1), "bye"),
+            decided(&[2], Some(2), "bye"),
+            decided(&[3], Some(3), "bye"),
+        ];
+        assert_eq!(swiss_bye(&standings, &previous), Some(2));
     }
 
-    for row in &board {
-        for cell in row {
-            if let Some(ref owner) = cell.owner {
-                *player_orb_counts.entry(owner.clone()).or_insert(0) += cell.orbs;
-            }
-        }
+    #[test]
+    fn swiss_round_puts_the_bye_last() {
+        let standings = vec![entry(1, 1), entry(2, 2), entry(3, 3), entry(4, 4), entry(5, 5)];
+        let previous = [decided(&[5], Some(5), "bye"), decided(&[1, 2], Some(1), "finished")];
+        let groups = swiss_round(standings, &previous);
+
+        let groups: Vec<Vec<Identity>> = groups.iter().map(|g| order(g)).collect();
+        assert_eq!(groups, vec![
+            vec![player(1), player(3)],
+            vec![player(2), player(5)],
+            vec![player(4)],
+        ]);
     }
 
-    // Check eliminations (only after first round)
-    if game_state.move_count >= players.len() as u32 {
-        for player in &players {
-            if *player_orb_counts.get(&player.color).unwrap_or(&0) == 0 && player.is_alive {
-                ctx.db.lobby_player().id().update(LobbyPlayer {
-                    id: player.id.clone(),
-                    lobby_id: player.lobby_id.clone(),
-                    identity: player.identity,
-                    address: player.address.clone(),
-                    name: player.name.clone(),
-                    color: player.color.clone(),
-                    is_host: player.is_host,
-                    is_alive: false,
-                    has_deposited: player.has_deposited,
-                    joined_at: player.joined_at,
-                });
-                log::info!("Player {} eliminated!", player.name);
-            }
-        }
+    // S beats X, M1 and W; X beats W; Y beats M1; M2 beats Y; M1 beats W.
+    // X, Y, M1 and M2 all finish on one win, and the two tiebreaks order them
+    // differently.
+    fn tiebreak_fixture() -> (Vec<TournamentEntry>, Vec<TournamentMatch>) {
+        let (s, x, y, m1, m2, w) = (1, 2, 3, 4, 5, 6);
+        let entries = vec![entry(s, 1), entry(x, 2), entry(y, 3), entry(m1, 4), entry(m2, 5), entry(w, 6)];
+        let matches = vec![
+            decided(&[s, x], Some(s), "finished"),
+            decided(&[x, w], Some(x), "finished"),
+            decided(&[y, m1], Some(y), "finished"),
+            decided(&[m2, y], Some(m2), "finished"),
+            decided(&[s, m1], Some(s), "finished"),
+            decided(&[s, w], Some(s), "finished"),
+            decided(&[m1, w], Some(m1), "finished"),
+        ];
+        (entries, matches)
     }
 
-    // Refresh alive players
-    let alive_players: Vec<_> = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .filter(|p| p.is_alive)
-        .collect();
+    #[test]
+    fn swiss_standings_rank_by_buchholz() {
+        let (entries, matches) = tiebreak_fixture();
+        let standings = swiss_standings(entries, &matches, Tiebreak::Buchholz);
 
-    let total_orbs: u32 = player_orb_counts.values().sum();
+        assert_eq!(order(&standings), vec![player(1), player(4), player(2), player(3), player(5), player(6)]);
+        assert_eq!(standings.iter().map(|e| e.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6]);
 
-    // Check for winner
-    if alive_players.len() == 1 && total_orbs > 0 {
-        let winner = &alive_players[0];
-        ctx.db.lobby().id().update(Lobby {
-            status: "finished".to_string(),
-            winner_identity: Some(winner.identity),
-            winner_address: Some(winner.address.clone()),
-            updated_at: ctx.timestamp,
-            ..lobby
-        });
-        log::info!("Game finished! Winner: {} ({})", winner.name, winner.address);
-        
-        // Update final board state
-        ctx.db.game_state().lobby_id().update(GameState {
-            board_json: serde_json::to_string(&board).unwrap(),
-            move_count: game_state.move_count + 1,
-            last_move_at: ctx.timestamp,
-            turn_lock_until: None,
-            last_move_player: Some(ctx.sender),
-            ..game_state
-        });
-        
-        // CRITICAL: Return early - game is over, no more moves allowed
-        return;
+        let m1 = &standings[1];
+        assert_eq!((m1.score, m1.buchholz, m1.sonneborn_berger), (1, 4, 0));
     }
 
-    // Record move
-    let move_id = format!("{}_{}", lobby_id, game_state.move_count);
-    ctx.db.game_move().insert(GameMove {
-        id: move_id,
-        lobby_id: lobby_id.clone(),
-        move_index: game_state.move_count,
-        player_identity: ctx.sender,
-        row,
-        col,
-        timestamp: ctx.timestamp,
-    });
-
-    // Update game state
-    let new_player_index = if alive_players.len() > 0 {
-        (game_state.current_player_index + 1) % alive_players.len() as u32
-    } else {
-        0
-    };
+    #[test]
+    fn swiss_standings_rank_by_sonneborn_berger() {
+        let (entries, matches) = tiebreak_fixture();
+        let standings = swiss_standings(entries, &matches, Tiebreak::SonnebornBerger);
 
-    // Set new turn deadline (30 seconds from now)
-    let new_deadline = ctx.timestamp + std::time::Duration::from_secs(30);
+        // Y and M2 each beat a one-win player; Buchholz then splits the pairs
+        assert_eq!(order(&standings), vec![player(1), player(3), player(5), player(4), player(2), player(6)]);
+    }
 
-    ctx.db.game_state().lobby_id().update(GameState {
-        board_json: serde_json::to_string(&board).unwrap(),
-        rows: game_state.rows,
-        cols: game_state.cols,
-        current_player_index: new_player_index,
-        move_count: game_state.move_count + 1,
-        last_move_at: ctx.timestamp,
-        turn_deadline: new_deadline,
-        turn_lock_until: None, // Release lock
-        last_move_player: Some(ctx.sender), // Track last player
-        ..game_state
-    });
-}
+    #[test]
+    fn swiss_standings_fall_back_to_seed() {
+        let entries = vec![entry(2, 2), entry(1, 1)];
+        let standings = swiss_standings(entries, &[decided(&[1], Some(1), "bye"), decided(&[2], Some(2), "bye")], Tiebreak::Buchholz);
+        assert_eq!(order(&standings), vec![player(1), player(2)]);
+    }
 
-/// Claim timeout for current player
-#[spacetimedb::reducer]
-pub fn claim_timeout(ctx: &ReducerContext, lobby_id: String) {
-    let lobby = ctx.db.lobby().id().find(&lobby_id)
-        .expect("Lobby not found");
+    // web3.js docs: web3.eth.accounts.sign("Some data", key)
+    const WEB3_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const WEB3_ADDRESS: &str = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+    const WEB3_SIGNATURE: &str = "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
 
-    if lobby.status != "live" {
-        panic!("Game is not live");
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
     }
 
-    let game_state = ctx.db.game_state().lobby_id().find(&lobby_id)
-        .expect("Game state not found");
+    /// personal_sign `message` with the web3.js key, returning r || s || v
+    fn personal_sign(message: &str) -> Vec<u8> {
+        use k256::ecdsa::SigningKey;
+
+        let key = SigningKey::from_slice(&decode_hex(WEB3_KEY).unwrap()).unwrap();
+        let prehash = Keccak256::new()
+            .chain_update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes())
+            .chain_update(message.as_bytes())
+            .finalize();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&prehash).unwrap();
 
-    // Check if deadline passed
-    if ctx.timestamp < game_state.turn_deadline {
-        panic!("Turn has not timed out yet");
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        bytes
     }
 
-    // Get alive players sorted by join time
-    let mut players: Vec<_> = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .filter(|p| p.is_alive)
-        .collect();
-    players.sort_by_key(|p| p.joined_at);
+    /// Swap s for n - s and flip v: the other valid signature for the same message
+    fn malleate(signature: &[u8]) -> Vec<u8> {
+        const N: [u8; 32] = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+            0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+        ];
+        let mut out = signature.to_vec();
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let diff = N[i] as i16 - signature[32 + i] as i16 - borrow;
+            borrow = (diff < 0) as i16;
+            out[32 + i] = diff.rem_euclid(256) as u8;
+        }
+        out[64] = if signature[64] == 27 { 28 } else { 27 };
+        out
+    }
 
-    if players.is_empty() {
-        panic!("No active players");
+    #[test]
+    fn recovers_web3_vector() {
+        assert_eq!(recover_personal_sign_address("Some data", WEB3_SIGNATURE).as_deref(), Some(WEB3_ADDRESS));
+        // Without the 0x prefix too
+        assert_eq!(recover_personal_sign_address("Some data", &WEB3_SIGNATURE[2..]).as_deref(), Some(WEB3_ADDRESS));
+        // A different message recovers someone else
+        assert_ne!(recover_personal_sign_address("Other data", WEB3_SIGNATURE).as_deref(), Some(WEB3_ADDRESS));
     }
 
-    // Identify timed out player
-    let current_idx = game_state.current_player_index as usize % players.len();
-    let timed_out_player = &players[current_idx];
+    #[test]
+    fn recovers_both_recovery_ids() {
+        let mut seen = HashSet::new();
+        for i in 0.. {
+            let message = format!("Some data {}", i);
+            let signature = personal_sign(&message);
+            assert_eq!(recover_personal_sign_address(&message, &to_hex(&signature)).as_deref(), Some(WEB3_ADDRESS));
 
-    // Eliminate player
-    ctx.db.lobby_player().id().update(LobbyPlayer {
-        is_alive: false,
-        ..timed_out_player.clone()
-    });
-    
-    log::info!("Player {} timed out!", timed_out_player.name);
+            // Wallets that report v as 0/1
+            let mut raw_v = signature.clone();
+            raw_v[64] -= 27;
+            assert_eq!(recover_personal_sign_address(&message, &to_hex(&raw_v)).as_deref(), Some(WEB3_ADDRESS));
 
-    // Refresh alive players
-    let alive_players: Vec<_> = ctx.db.lobby_player()
-        .lobby_id()
-        .filter(&lobby_id)
-        .filter(|p| p.is_alive)
-        .collect();
+            seen.insert(signature[64]);
+            if seen.len() == 2 {
+                break;
+            }
+        }
+        assert_eq!(seen, HashSet::from([27, 28]));
+    }
 
-    // Check for winner
-    if alive_players.len() == 1 {
-        let winner = &alive_players[0];
-        ctx.db.lobby().id().update(Lobby {
-            status: "finished".to_string(),
-            winner_identity: Some(winner.identity),
-            winner_address: Some(winner.address.clone()),
-            updated_at: ctx.timestamp,
-            ..lobby
-        });
-        log::info!("Game finished by timeout! Winner: {} ({})", winner.name, winner.address);
-    } else {
-        // Advance turn
-        let new_player_index = if alive_players.len() > 0 {
-            // We don't increment index because the current player was removed, 
-            // so the next player falls into the same index (modulo new length)
-            // But we need to be careful about the modulo logic.
-            // If we had [A, B, C] and B (index 1) timed out. New list [A, C].
-            // We want C to play. C is now at index 1. So index stays 1.
-            // If C (index 2) timed out. New list [A, B]. We want A to play. Index 0.
-            // So we just take current_index % new_length.
-            game_state.current_player_index % alive_players.len() as u32
-        } else {
-            0
-        };
+    #[test]
+    fn recovers_high_s_signatures() {
+        let signature = decode_hex(WEB3_SIGNATURE).unwrap();
+        let high_s = malleate(&signature);
+        assert!(high_s[32] >= 0x80);
+        assert_eq!(recover_personal_sign_address("Some data", &to_hex(&high_s)).as_deref(), Some(WEB3_ADDRESS));
+    }
 
-        // Set new turn deadline
-        let new_deadline = ctx.timestamp + std::time::Duration::from_secs(30);
+    #[test]
+    fn rejects_malformed_signatures() {
+        assert_eq!(recover_personal_sign_address("Some data", "0x1234"), None);
+        assert_eq!(recover_personal_sign_address("Some data", &WEB3_SIGNATURE[..WEB3_SIGNATURE.len() - 1]), None);
 
-        ctx.db.game_state().lobby_id().update(GameState {
-            current_player_index: new_player_index,
-            turn_deadline: new_deadline,
-            ..game_state
+        let mut bad_v = decode_hex(WEB3_SIGNATURE).unwrap();
+        bad_v[64] = 35;
+        assert_eq!(recover_personal_sign_address("Some data", &to_hex(&bad_v)), None);
+    }
+
+    fn siwe_text(domain: &str, uri: &str, chain_id: u32) -> String {
+        format!(
+            "{} wants you to sign in with your Ethereum account:\n\
+             0x2C7536E3605D9C16a7a3D7b1898e529396a65c23\n\
+             \n\
+             Link this wallet to your player.\n\
+             \n\
+             URI: {}\n\
+             Version: 1\n\
+             Chain ID: {}\n\
+             Nonce: abc123\n\
+             Issued At: 2026-01-01T00:00:00Z\n\
+             Resources:\n\
+             - farcaster://fid/42",
+            domain, uri, chain_id,
+        )
+    }
+
+    #[test]
+    fn parses_siwe_message() {
+        let siwe = parse_siwe_message(&siwe_text(SIWE_DOMAIN, "https://onchainreaction.vercel.app/online", 8453)).unwrap();
+        assert_eq!(siwe, SiweMessage {
+            domain: SIWE_DOMAIN.to_string(),
+            address: WEB3_ADDRESS.to_string(),
+            uri: "https://onchainreaction.vercel.app/online".to_string(),
+            version: "1".to_string(),
+            chain_id: 8453,
+            nonce: "abc123".to_string(),
+            resources: vec!["farcaster://fid/42".to_string()],
         });
+        assert!(siwe.is_for_our_site());
     }
-}
 
-/// Leave lobby (before game starts)
-#[spacetimedb::reducer]
-pub fn leave_lobby(ctx: &ReducerContext, lobby_id: String) {
-    let lobby = ctx.db.lobby().id().find(&lobby_id)
-        .expect("Lobby not found");
+    #[test]
+    fn siwe_checks_domain_and_uri() {
+        let check = |domain: &str, uri: &str| parse_siwe_message(&siwe_text(domain, uri, 8453)).unwrap().is_for_our_site();
+        assert!(check(SIWE_DOMAIN, "https://onchainreaction.vercel.app"));
+        assert!(!check("evil.example", "https://onchainreaction.vercel.app"));
+        assert!(!check(SIWE_DOMAIN, "https://evil.example"));
+        assert!(!check(SIWE_DOMAIN, "https://onchainreaction.vercel.app.evil.example"));
+        assert!(!check(SIWE_DOMAIN, "http://onchainreaction.vercel.app"));
+    }
 
-    if lobby.status != "waiting" {
-        panic!("Cannot leave after game started");
+    #[test]
+    fn rejects_malformed_siwe_messages() {
+        assert_eq!(parse_siwe_message("Nonce: abc123"), None);
+        let text = siwe_text(SIWE_DOMAIN, "https://onchainreaction.vercel.app", 8453);
+        assert_eq!(parse_siwe_message(&text.replace("Chain ID: 8453", "Chain ID: base")), None);
+        assert_eq!(parse_siwe_message(&text.replace("Nonce: abc123\n", "")), None);
     }
 
-    let player_id = format!("{}_{:?}", lobby_id, ctx.sender);
-    let player = ctx.db.lobby_player().id().find(&player_id)
-        .expect("Not in this lobby");
+    #[test]
+    fn net_prize_pool_deducts_contract_fee() {
+        // 0.5% of a 2 USDC pool
+        assert_eq!(net_prize_pool(u256::from(1_000_000u32), 2), Some(u256::from(1_990_000u32)));
+        assert_eq!(net_prize_pool(u256::ZERO, 5), Some(u256::ZERO));
+        assert!(net_prize_pool(u256::from(MAX_ENTRY_FEE), 5).is_some());
+        assert_eq!(net_prize_pool(u256::MAX, 2), None);
+    }
 
-    if player.is_host {
-        // Cancel lobby if host leaves
-        ctx.db.lobby().id().update(Lobby {
-            status: "cancelled".to_string(),
-            updated_at: ctx.timestamp,
-            ..lobby
-        });
+    #[test]
+    fn parse_entry_fee_is_capped() {
+        assert_eq!(parse_entry_fee("1000000"), Ok(u256::from(1_000_000u32)));
+        assert_eq!(parse_entry_fee(&MAX_ENTRY_FEE.to_string()), Ok(u256::from(MAX_ENTRY_FEE)));
+        assert_eq!(parse_entry_fee(&(MAX_ENTRY_FEE + 1).to_string()), Err(ErrorCode::EntryFeeTooHigh));
+        assert_eq!(parse_entry_fee("-1"), Err(ErrorCode::InvalidAmount));
+    }
 
-        // Remove all players
-        let all_players: Vec<_> = ctx.db.lobby_player()
-            .lobby_id()
-            .filter(&lobby_id)
-            .collect();
+    #[test]
+    fn charge_clock_adds_increment_only_while_time_remains() {
+        assert_eq!(charge_clock(10_000, 4_000, 2), Some(8_000));
+        assert_eq!(charge_clock(10_000, 10_000, 2), Some(2_000));
+        // A backdated turn that overran the bank flags instead of flooring at zero
+        assert_eq!(charge_clock(10_000, 10_001, 2), None);
+        assert_eq!(charge_clock(0, 1, 60), None);
+    }
 
-        for p in all_players {
-            ctx.db.lobby_player().id().delete(&p.id);
+    fn seated(n: u8, eliminated_at_move: Option<u32>) -> LobbyPlayer {
+        LobbyPlayer {
+            id: format!("l_{}", n),
+            lobby_id: "l".to_string(),
+            identity: player(n),
+            address: String::new(),
+            name: format!("player {}", n),
+            color: String::new(),
+            is_host: n == 1,
+            is_alive: eliminated_at_move.is_none(),
+            has_deposited: true,
+            is_ready: true,
+            time_bank_ms: 0,
+            eliminated_at_move,
+            fid: None,
+            joined_at: Timestamp::UNIX_EPOCH,
         }
+    }
 
-        log::info!("Lobby {} cancelled by host", lobby_id);
-    } else {
-        ctx.db.lobby_player().id().delete(&player_id);
+    fn invite(identity: Option<u8>, address: Option<&str>) -> LobbyInvite {
+        LobbyInvite {
+            id: 0,
+            lobby_id: "l".to_string(),
+            address: address.map(str::to_string),
+            identity: identity.map(player),
+            invited_by: player(1),
+            created_at: Timestamp::UNIX_EPOCH,
+            expires_at: Timestamp::UNIX_EPOCH,
+            claimed_by: None,
+        }
+    }
 
-        ctx.db.lobby().id().update(Lobby {
-            updated_at: ctx.timestamp,
-            ..lobby
-        });
+    #[test]
+    fn reserved_seats_count_distinct_unseated_invitees() {
+        let players = vec![seated(1, None), seated(2, None)];
+        let invites = vec![
+            invite(Some(2), None),              // already seated
+            invite(Some(3), Some("0xabc")),
+            invite(None, Some("0xabc")),        // same invitee by wallet
+            invite(Some(3), None),              // same invitee by identity
+            invite(Some(4), None),
+        ];
+        assert_eq!(count_reserved_seats(&invites, &players, None), 2);
+        // The joiner's own invite is the seat they take
+        assert_eq!(count_reserved_seats(&invites, &players, Some((player(4), "0xdef"))), 1);
+        assert_eq!(count_reserved_seats(&invites, &players, Some((player(3), "0xABC"))), 1);
+    }
 
-        log::info!("Player {:?} left lobby {}", ctx.sender, lobby_id);
+    fn placements_of(winner: Option<u8>, players: Vec<LobbyPlayer>) -> Vec<(Identity, u32)> {
+        final_placements(winner.map(player), players)
+            .into_iter()
+            .map(|(p, placement)| (p.identity, placement))
+            .collect()
     }
-}
 
-/// Get all lobbies (for listing)
-#[spacetimedb::reducer]
-pub fn ping(ctx: &ReducerContext) {
-    log::info!("Ping from {:?}", ctx.sender);
+    #[test]
+    fn final_placements_rank_by_elimination() {
+        let players = vec![seated(4, Some(5)), seated(2, Some(12)), seated(1, None), seated(3, Some(9))];
+        assert_eq!(placements_of(Some(1), players), vec![
+            (player(1), 1),
+            (player(2), 2),
+            (player(3), 3),
+            (player(4), 4),
+        ]);
+    }
+
+    #[test]
+    fn final_placements_share_a_place_for_the_same_move() {
+        let players = vec![seated(1, None), seated(2, Some(10)), seated(3, Some(10)), seated(4, Some(3))];
+        let placements = placements_of(Some(1), players);
+        assert_eq!(placements[0], (player(1), 1));
+        assert_eq!(placements[1].1, 2);
+        assert_eq!(placements[2].1, 2);
+        assert_eq!(placements[3], (player(4), 4));
+    }
+
+    #[test]
+    fn final_placements_without_a_winner() {
+        // Nobody won: survivors tie ahead of anyone knocked out
+        let players = vec![seated(1, Some(4)), seated(2, None), seated(3, None)];
+        assert_eq!(placements_of(None, players), vec![(player(2), 1), (player(3), 1), (player(1), 3)]);
+    }
 }
+